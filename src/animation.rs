@@ -0,0 +1,231 @@
+use std::time::Duration;
+
+use bevy::prelude::*;
+
+use crate::{Car, CarState, DamageState, LevelAssets};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ClipKind {
+    Idle,
+    Crash,
+    Dented,
+    Wrecked,
+}
+
+/// Frames for one animation, each a separate image that gets packed into the
+/// level's atlas once loaded.
+#[derive(Clone, Default)]
+pub struct SpriteClip {
+    pub frames: Vec<Handle<Image>>,
+    pub fps: f32,
+}
+
+impl SpriteClip {
+    pub fn new(frames: Vec<Handle<Image>>, fps: f32) -> Self {
+        SpriteClip { frames, fps }
+    }
+}
+
+/// Every clip a single skin can play, declared per level in `LevelAssets`.
+#[derive(Clone, Default)]
+pub struct SpriteAnimations {
+    pub clips: Vec<(ClipKind, SpriteClip)>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SheetId {
+    Car,
+    Obstacle(usize),
+}
+
+pub struct BuiltSheet {
+    pub texture: Handle<Image>,
+    pub layout: Handle<TextureAtlasLayout>,
+    clips: Vec<(ClipKind, Vec<usize>, f32)>,
+}
+
+impl BuiltSheet {
+    /// Atlas indices and frame rate for `kind`, falling back to the idle clip
+    /// when the skin has no frames for it.
+    fn clip(&self, kind: ClipKind) -> Option<(&[usize], f32)> {
+        self.clips
+            .iter()
+            .find(|(clip, frames, _)| *clip == kind && !frames.is_empty())
+            .or_else(|| {
+                self.clips
+                    .iter()
+                    .find(|(clip, _, _)| *clip == ClipKind::Idle)
+            })
+            .map(|(_, frames, fps)| (frames.as_slice(), *fps))
+    }
+}
+
+#[derive(Resource, Default)]
+pub struct LevelAtlases {
+    pub car: Option<BuiltSheet>,
+    pub obstacles: Vec<Option<BuiltSheet>>,
+    pub ready: bool,
+}
+
+impl LevelAtlases {
+    fn sheet(&self, id: SheetId) -> Option<&BuiltSheet> {
+        match id {
+            SheetId::Car => self.car.as_ref(),
+            SheetId::Obstacle(index) => self.obstacles.get(index)?.as_ref(),
+        }
+    }
+}
+
+#[derive(Component)]
+pub struct SpriteAnimator {
+    pub sheet: SheetId,
+    pub clip: ClipKind,
+    pub frame: usize,
+    pub timer: Timer,
+}
+
+impl SpriteAnimator {
+    pub fn new(sheet: SheetId) -> Self {
+        SpriteAnimator {
+            sheet,
+            clip: ClipKind::Idle,
+            frame: 0,
+            timer: Timer::from_seconds(1.0, TimerMode::Repeating),
+        }
+    }
+
+    pub fn play(&mut self, clip: ClipKind) {
+        if self.clip != clip {
+            self.clip = clip;
+            self.frame = 0;
+        }
+    }
+}
+
+pub fn reset_level_atlases(mut atlases: ResMut<LevelAtlases>) {
+    *atlases = LevelAtlases::default();
+}
+
+fn build_sheet(
+    animations: &SpriteAnimations,
+    images: &mut Assets<Image>,
+    layouts: &mut Assets<TextureAtlasLayout>,
+) -> Option<BuiltSheet> {
+    let mut builder = TextureAtlasBuilder::default();
+    let mut added: Vec<AssetId<Image>> = Vec::new();
+    for (_, clip) in animations.clips.iter() {
+        for frame in clip.frames.iter() {
+            if !added.contains(&frame.id()) {
+                added.push(frame.id());
+                builder.add_texture(Some(frame.id()), images.get(frame)?);
+            }
+        }
+    }
+    let (layout, texture) = builder.build().ok()?;
+
+    let clips = animations
+        .clips
+        .iter()
+        .map(|(kind, clip)| {
+            let frames = clip
+                .frames
+                .iter()
+                .filter_map(|frame| layout.get_texture_index(frame))
+                .collect();
+            (*kind, frames, clip.fps)
+        })
+        .collect();
+
+    Some(BuiltSheet {
+        texture: images.add(texture),
+        layout: layouts.add(layout),
+        clips,
+    })
+}
+
+pub fn build_level_atlases(
+    level_assets: Res<LevelAssets>,
+    mut atlases: ResMut<LevelAtlases>,
+    mut images: ResMut<Assets<Image>>,
+    mut layouts: ResMut<Assets<TextureAtlasLayout>>,
+) {
+    if atlases.ready {
+        return;
+    }
+
+    let all_loaded = std::iter::once(&level_assets.car_animations)
+        .chain(level_assets.obstacle_animations.iter())
+        .flat_map(|animations| animations.clips.iter())
+        .flat_map(|(_, clip)| clip.frames.iter())
+        .all(|frame| images.contains(frame));
+    if !all_loaded {
+        return;
+    }
+
+    atlases.car = build_sheet(&level_assets.car_animations, &mut images, &mut layouts);
+    atlases.obstacles = level_assets
+        .obstacle_animations
+        .iter()
+        .map(|animations| build_sheet(animations, &mut images, &mut layouts))
+        .collect();
+    atlases.ready = true;
+}
+
+/// Skins without damage frames keep playing idle, with only the tint from
+/// `update_damage_state` to show the damage.
+pub fn select_car_clip(mut cars: Query<(&Car, &mut SpriteAnimator)>) {
+    for (car, mut animator) in cars.iter_mut() {
+        animator.play(match (&car.state, DamageState::from_health(car.health)) {
+            (CarState::Crashed, _) => ClipKind::Crash,
+            (CarState::Moving, DamageState::Intact) => ClipKind::Idle,
+            (CarState::Moving, DamageState::Dented) => ClipKind::Dented,
+            (CarState::Moving, DamageState::Wrecked) => ClipKind::Wrecked,
+        });
+    }
+}
+
+pub fn animate_sprites(
+    mut commands: Commands,
+    time: Res<Time>,
+    atlases: Res<LevelAtlases>,
+    mut sprites: Query<(
+        Entity,
+        &mut SpriteAnimator,
+        &mut Handle<Image>,
+        Option<&mut TextureAtlas>,
+    )>,
+) {
+    for (entity, mut animator, mut texture, atlas) in sprites.iter_mut() {
+        let Some(sheet) = atlases.sheet(animator.sheet) else {
+            continue;
+        };
+        let Some((frames, fps)) = sheet.clip(animator.clip) else {
+            continue;
+        };
+        if frames.is_empty() {
+            continue;
+        }
+
+        if fps > 0.0 {
+            animator
+                .timer
+                .set_duration(Duration::from_secs_f32(1.0 / fps));
+            if animator.timer.tick(time.delta()).just_finished() {
+                animator.frame += 1;
+            }
+        }
+        animator.frame %= frames.len();
+        let index = frames[animator.frame];
+
+        match atlas {
+            Some(mut atlas) => atlas.index = index,
+            None => {
+                *texture = sheet.texture.clone();
+                commands.entity(entity).insert(TextureAtlas {
+                    layout: sheet.layout.clone(),
+                    index,
+                });
+            }
+        }
+    }
+}
@@ -0,0 +1,149 @@
+use bevy::prelude::*;
+
+use crate::{
+    autopilot_plan, steer_towards, Car, Countdown, GameState, GarageUi, Level, LevelAssetMarker,
+    LevelAssets, MenuText, Obstacle, Score, SelectedCar,
+};
+
+/// Time on the garage screen without a key press before the demo starts.
+const IDLE_SECONDS: f32 = 20.0;
+
+/// The title screen demo, where the autopilot drives the first level until a
+/// key is pressed.
+#[derive(Resource)]
+pub struct AttractMode {
+    pub active: bool,
+    idle: Timer,
+    /// Level, car and score to go back to once the demo ends.
+    saved: (u8, usize, f32),
+}
+
+impl Default for AttractMode {
+    fn default() -> Self {
+        AttractMode {
+            active: false,
+            idle: Timer::from_seconds(IDLE_SECONDS, TimerMode::Once),
+            saved: (1, 0, 0.0),
+        }
+    }
+}
+
+#[derive(Component)]
+pub struct AttractUi;
+
+/// Everything the demo put on screen, cleared away when it ends.
+type DemoEntities = Or<(
+    With<LevelAssetMarker>,
+    With<MenuText>,
+    With<AttractUi>,
+    With<Countdown>,
+)>;
+
+/// Run condition for anything the demo must not touch, like the save file.
+pub fn player_driving(attract: Res<AttractMode>) -> bool {
+    !attract.active
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn start_attract_mode(
+    mut commands: Commands,
+    time: Res<Time<Real>>,
+    button_input: Res<ButtonInput<KeyCode>>,
+    asset_server: Res<AssetServer>,
+    mut attract: ResMut<AttractMode>,
+    mut level: ResMut<Level>,
+    mut selected: ResMut<SelectedCar>,
+    mut score: ResMut<Score>,
+    garage: Query<Entity, With<GarageUi>>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    if button_input.get_pressed().next().is_some() {
+        attract.idle.reset();
+        return;
+    }
+    if !attract.idle.tick(time.delta()).just_finished() {
+        return;
+    }
+
+    garage
+        .iter()
+        .for_each(|entity| commands.entity(entity).despawn());
+    attract.active = true;
+    attract.saved = (level.level, selected.index, score.score);
+    level.level = 1;
+    selected.index = 0;
+    score.score = 0.0;
+    commands.spawn((
+        TextBundle::from_section(
+            "DEMO   PRESS ANY KEY",
+            TextStyle {
+                font_size: 30.0,
+                font: asset_server.load("GohuFont11NerdFont-Regular.ttf"),
+                ..default()
+            },
+        )
+        .with_text_justify(JustifyText::Center)
+        .with_style(Style {
+            position_type: PositionType::Absolute,
+            top: Val::Percent(85.0),
+            left: Val::Percent(25.0),
+            width: Val::Percent(50.0),
+            ..default()
+        }),
+        AttractUi,
+    ));
+    next_state.set(GameState::LoadNextLevel);
+}
+
+/// Steers for the demo in place of the player, and ends it on a key press,
+/// a game over or once the laps are done. Runs before anything reads input.
+#[allow(clippy::too_many_arguments)]
+pub fn drive_attract_mode(
+    mut commands: Commands,
+    state: Res<State<GameState>>,
+    mut next_state: ResMut<NextState<GameState>>,
+    mut attract: ResMut<AttractMode>,
+    mut button_input: ResMut<ButtonInput<KeyCode>>,
+    car: Query<(&Car, &Transform)>,
+    obstacles: Query<(&Obstacle, &Transform)>,
+    level_assets: Res<LevelAssets>,
+    mut level: ResMut<Level>,
+    mut selected: ResMut<SelectedCar>,
+    mut score: ResMut<Score>,
+    mut time: ResMut<Time<Virtual>>,
+    demo: Query<Entity, DemoEntities>,
+) {
+    if !attract.active {
+        return;
+    }
+    let key_pressed = button_input.get_just_pressed().next().is_some();
+    button_input.reset_all();
+
+    // Loading screens are left to finish, so the level is never half built.
+    let over = match state.get() {
+        GameState::Countdown => key_pressed,
+        GameState::Running => {
+            key_pressed
+                || car.get_single().is_ok_and(|(_, transform)| {
+                    level_assets.laps_completed(transform.translation.x) >= level_assets.laps
+                })
+        }
+        GameState::Paused => true,
+        _ => false,
+    };
+    if !over {
+        if let (GameState::Running, Ok((car, transform))) = (state.get(), car.get_single()) {
+            let plan = autopilot_plan(car, transform.translation, obstacles.iter(), &level_assets);
+            steer_towards(&mut button_input, transform.translation.y, plan.target);
+        }
+        return;
+    }
+
+    demo.iter()
+        .for_each(|entity| commands.entity(entity).despawn());
+    time.set_relative_speed(1.0);
+    (level.level, selected.index, score.score) = attract.saved;
+    attract.active = false;
+    attract.idle.reset();
+    next_state.set(GameState::Garage);
+}
@@ -0,0 +1,262 @@
+use bevy::prelude::*;
+
+use crate::{lane_y, Car, CarStats, Footprint, LevelAssets, Obstacle, LANES};
+
+/// How far ahead the autopilot plans, in seconds.
+const LOOKAHEAD_SECONDS: f32 = 2.5;
+/// Time between planned steering decisions.
+const PLAN_STEP: f32 = 0.05;
+/// Planned heights per distance the car can steer in one `PLAN_STEP`, so
+/// narrow gaps between the rows still get found.
+const ROWS_PER_STEP: i32 = 2;
+/// Extra room kept ahead of and behind anything the car passes, in world units.
+const CLEARANCE: f32 = 10.0;
+/// Extra room kept beside anything the car passes, which also covers the car
+/// sitting between two planned rows. Adjacent lanes are only just far enough
+/// apart to pass in, so this has to stay small.
+const SIDE_CLEARANCE: f32 = 4.0;
+
+/// Something on the road the autopilot has to steer around.
+#[derive(Clone, Copy, Debug)]
+pub struct Hazard {
+    /// Everywhere the hazard may move to sideways, see `Obstacle::footprint`.
+    pub footprint: Footprint,
+    /// Size of the hazard itself, which is what collisions are checked against.
+    pub size: Vec2,
+    /// Slowest and fastest the hazard may go along the road, negative for
+    /// oncoming traffic.
+    pub speeds: (f32, f32),
+}
+
+impl Hazard {
+    /// Whether a car of `car_size` at `car_pos` would be hit by the hazard
+    /// `time` seconds from now, with the same leeway as `detect_collision`.
+    /// Anywhere the hazard could have got to by then counts.
+    fn blocks(&self, car_pos: Vec2, car_size: Vec2, time: f32) -> bool {
+        let footprint = self.footprint;
+        let (slowest, fastest) = (self.speeds.0 * time, self.speeds.1 * time);
+        let reach = 0.95 * (car_size.x + self.size.x) / 2.0 + CLEARANCE;
+        let side = 0.90 * (car_size.y + self.size.y) / 2.0
+            + (footprint.size.y - self.size.y) / 2.0
+            + SIDE_CLEARANCE;
+        car_pos.x > footprint.x + slowest - reach
+            && car_pos.x < footprint.x + fastest + reach
+            && (footprint.y - car_pos.y).abs() < side
+    }
+}
+
+/// Where the autopilot steers next, and whether it could see a way through.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Plan {
+    pub target: f32,
+    /// False when every way of steering runs into something before the end
+    /// of the lookahead.
+    pub clear: bool,
+}
+
+/// How far a car going at `speed` covers in `time` while it accelerates
+/// towards its top speed, as `update_car` drives it.
+fn travelled(speed: f32, stats: &CarStats, time: f32) -> f32 {
+    if speed >= stats.max_speed || stats.acceleration <= 0.0 {
+        return speed * time;
+    }
+    let ramp = ((stats.max_speed - speed) / stats.acceleration).min(time);
+    speed * ramp + stats.acceleration * ramp * ramp / 2.0 + stats.max_speed * (time - ramp)
+}
+
+/// Picks the height to steer for next, between the outermost `lanes`.
+///
+/// Plans `LOOKAHEAD_SECONDS` ahead over every way the car could steer each
+/// `PLAN_STEP`, and takes the first move of whichever keeps the most ways
+/// open at the furthest point it can get to without being hit. Smaller
+/// moves win ties, so the car holds its line on an empty road.
+pub fn autopilot_steer(
+    car_pos: Vec3,
+    speed: Vec2,
+    stats: &CarStats,
+    lanes: &[f32],
+    hazards: &[Hazard],
+) -> Plan {
+    let car_size = stats.size();
+    let spacing = speed.y * PLAN_STEP / ROWS_PER_STEP as f32;
+    if spacing <= 0.0 {
+        return Plan {
+            target: car_pos.y,
+            clear: true,
+        };
+    }
+    let top = lanes.iter().copied().fold(car_pos.y, f32::max);
+    let bottom = lanes.iter().copied().fold(car_pos.y, f32::min);
+
+    // Rows sit at fixed heights rather than moving with the car, so the plan
+    // holds still from one frame to the next. The car counts as being in the
+    // nearest one, and the outermost rows sit on the outermost lanes.
+    let lowest = (bottom / spacing).floor() as i32;
+    let highest = (top / spacing).ceil() as i32;
+    let row_y = |row: i32| (row as f32 * spacing).clamp(bottom, top);
+    let rows = (highest - lowest + 1) as usize;
+    let current = ((car_pos.y / spacing).round() as i32).clamp(lowest, highest);
+
+    let steps = (LOOKAHEAD_SECONDS / PLAN_STEP) as usize;
+    let end = car_pos.x + travelled(speed.x, stats, LOOKAHEAD_SECONDS);
+    let hazards: Vec<&Hazard> = hazards
+        .iter()
+        .filter(|hazard| {
+            let reach = hazard.footprint.size.x
+                + car_size.x
+                + hazard.speeds.0.abs().max(hazard.speeds.1.abs()) * LOOKAHEAD_SECONDS;
+            hazard.footprint.x + reach > car_pos.x && hazard.footprint.x - reach < end
+        })
+        .collect();
+    let free: Vec<Vec<bool>> = (0..=steps)
+        .map(|step| {
+            let time = step as f32 * PLAN_STEP;
+            let x = car_pos.x + travelled(speed.x, stats, time);
+            (lowest..=highest)
+                .map(|row| {
+                    let position = Vec2::new(x, row_y(row));
+                    hazards
+                        .iter()
+                        .all(|hazard| !hazard.blocks(position, car_size, time))
+                })
+                .collect()
+        })
+        .collect();
+
+    // For each reachable row, the move made on the first step to get there.
+    let mut reached: Vec<Option<i32>> = vec![None; rows];
+    reached[(current - lowest) as usize] = Some(0);
+    let mut clear = true;
+    for step in 1..=steps {
+        let mut next: Vec<Option<i32>> = vec![None; rows];
+        for (from, first) in reached.iter().enumerate() {
+            let Some(first) = *first else {
+                continue;
+            };
+            let nearest = from.saturating_sub(ROWS_PER_STEP as usize);
+            let furthest = (from + ROWS_PER_STEP as usize).min(rows - 1);
+            for (to, route) in next.iter_mut().enumerate().take(furthest + 1).skip(nearest) {
+                // Every row swept through has to be clear for the whole step.
+                let (low, high) = (from.min(to), from.max(to));
+                if !(low..=high).all(|row| free[step - 1][row] && free[step][row]) {
+                    continue;
+                }
+                let first = if step == 1 {
+                    to as i32 - from as i32
+                } else {
+                    first
+                };
+                *route = match *route {
+                    Some(other) if other.abs() <= first.abs() => Some(other),
+                    _ => Some(first),
+                };
+            }
+        }
+        if next.iter().all(Option::is_none) {
+            clear = false;
+            break;
+        }
+        reached = next;
+    }
+
+    let open = |first: i32| reached.iter().filter(|row| **row == Some(first)).count();
+    let best = (1..=ROWS_PER_STEP)
+        .flat_map(|row| [row, -row])
+        .fold(0, |best, first| {
+            if open(first) > open(best) {
+                first
+            } else {
+                best
+            }
+        });
+    Plan {
+        target: row_y(current + best),
+        clear,
+    }
+}
+
+/// What the autopilot would do with `car`, read from the live obstacles.
+pub fn autopilot_plan<'a>(
+    car: &Car,
+    car_pos: Vec3,
+    obstacles: impl Iterator<Item = (&'a Obstacle, &'a Transform)>,
+    level_assets: &LevelAssets,
+) -> Plan {
+    let lanes: Vec<f32> = (0..LANES)
+        .map(|lane| lane_y(&level_assets.y_values, lane))
+        .collect();
+    let hazards: Vec<Hazard> = obstacles
+        .map(|(obstacle, transform)| Hazard {
+            footprint: obstacle.footprint(transform.translation),
+            size: obstacle.size,
+            speeds: obstacle.speed_range(),
+        })
+        .collect();
+    autopilot_steer(car_pos, car.speed, &car.stats, &lanes, &hazards)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::DEFAULT_STATS;
+
+    const Y_VALUES: [f32; 4] = [72.5, 197.5, 337.5, 457.5];
+    const SIZE: Vec2 = Vec2::new(135.0, 105.0);
+
+    fn lanes() -> Vec<f32> {
+        (0..LANES).map(|lane| lane_y(&Y_VALUES, lane)).collect()
+    }
+
+    fn parked(x: f32, y: f32) -> Hazard {
+        Hazard {
+            footprint: Footprint { x, y, size: SIZE },
+            size: SIZE,
+            speeds: (0.0, 0.0),
+        }
+    }
+
+    /// Plan for a car at full speed in the lane just above the middle.
+    fn plan(hazards: &[Hazard]) -> Plan {
+        let speed = Vec2::new(DEFAULT_STATS.max_speed, DEFAULT_STATS.lateral_speed);
+        autopilot_steer(
+            Vec3::new(0.0, Y_VALUES[0], 0.0),
+            speed,
+            &DEFAULT_STATS,
+            &lanes(),
+            hazards,
+        )
+    }
+
+    #[test]
+    fn empty_road_holds_the_line() {
+        let plan = plan(&[]);
+        assert!(plan.clear);
+        assert!((plan.target - Y_VALUES[0]).abs() < 10.0);
+    }
+
+    #[test]
+    fn blocked_lane_is_left() {
+        // Close enough that waiting even one step leaves no way around.
+        let plan = plan(&[parked(390.0, Y_VALUES[0])]);
+        assert!(plan.clear);
+        assert!((plan.target - Y_VALUES[0]).abs() > 10.0);
+    }
+
+    #[test]
+    fn gap_in_a_far_lane_is_steered_for() {
+        let wall: Vec<Hazard> = lanes()
+            .into_iter()
+            .filter(|y| *y != Y_VALUES[3])
+            .map(|y| parked(2200.0, y))
+            .collect();
+        let plan = plan(&wall);
+        assert!(plan.clear);
+        assert!(plan.target > Y_VALUES[0]);
+    }
+
+    #[test]
+    fn full_wall_is_a_dead_end() {
+        let wall: Vec<Hazard> = lanes().into_iter().map(|y| parked(1000.0, y)).collect();
+        assert!(!plan(&wall).clear);
+    }
+}
@@ -0,0 +1,212 @@
+//! Plays levels headlessly with a scripted or AI driver and reports how they went,
+//! for judging difficulty without playing.
+
+use std::process;
+use std::thread;
+
+use midnight_motorist::{simulate, Driver, RunReport, LEVELS, ROSTER};
+
+const USAGE: &str = "\
+usage: simulate [options]
+
+  --level <n>          level to run, every level by default
+  --runs <n>           seeded runs per level, 1000 by default
+  --seed <n>           first seed; run i uses seed + i, 0 by default
+  --driver <name>      straight, weave or autopilot, autopilot by default
+  --car <n>            roster index of the car, 0 by default
+  --seconds <n>        longest a run may take, 300 by default
+  --format <name>      csv for one row per run, json for a summary per level
+  --threads <n>        worker threads, one per core by default";
+
+struct Options {
+    levels: Vec<u8>,
+    runs: u64,
+    seed: u64,
+    driver: Driver,
+    car: usize,
+    seconds: f32,
+    json: bool,
+    threads: usize,
+}
+
+fn number<T: std::str::FromStr>(name: &str, value: Option<String>) -> Result<T, String> {
+    let value = value.ok_or_else(|| format!("{} needs a value", name))?;
+    value
+        .parse()
+        .map_err(|_| format!("invalid {} '{}'", name, value))
+}
+
+fn parse(mut args: impl Iterator<Item = String>) -> Result<Options, String> {
+    let mut options = Options {
+        levels: (1..=LEVELS.len() as u8).collect(),
+        runs: 1000,
+        seed: 0,
+        driver: Driver::Autopilot,
+        car: 0,
+        seconds: 300.0,
+        json: false,
+        threads: thread::available_parallelism().map_or(1, |threads| threads.get()),
+    };
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--level" => {
+                let level: u8 = number("level", args.next())?;
+                if !(1..=LEVELS.len() as u8).contains(&level) {
+                    return Err(format!("level must be 1 to {}", LEVELS.len()));
+                }
+                options.levels = vec![level];
+            }
+            "--runs" => options.runs = number("runs", args.next())?,
+            "--seed" => options.seed = number("seed", args.next())?,
+            "--driver" => {
+                let name = args.next().unwrap_or_default();
+                options.driver =
+                    Driver::from_name(&name).ok_or_else(|| format!("unknown driver '{}'", name))?;
+            }
+            "--car" => {
+                options.car = number("car", args.next())?;
+                if options.car >= ROSTER.len() {
+                    return Err(format!("car must be below {}", ROSTER.len()));
+                }
+            }
+            "--seconds" => options.seconds = number("seconds", args.next())?,
+            "--format" => {
+                options.json = match args.next().as_deref() {
+                    Some("csv") => false,
+                    Some("json") => true,
+                    _ => return Err("format must be csv or json".to_string()),
+                }
+            }
+            "--threads" => options.threads = number::<usize>("threads", args.next())?.max(1),
+            "--help" | "-h" => {
+                println!("{}", USAGE);
+                process::exit(0);
+            }
+            _ => return Err(format!("unknown option '{}'", arg)),
+        }
+    }
+    Ok(options)
+}
+
+/// Runs every seed for `level`, split across `options.threads` workers.
+fn run_level(level: u8, options: &Options) -> Vec<RunReport> {
+    let seeds: Vec<u64> = (0..options.runs)
+        .map(|run| options.seed.wrapping_add(run))
+        .collect();
+    let chunk = seeds.len().div_ceil(options.threads).max(1);
+    thread::scope(|scope| {
+        let workers: Vec<_> = seeds
+            .chunks(chunk)
+            .map(|seeds| {
+                scope.spawn(move || {
+                    seeds
+                        .iter()
+                        .map(|seed| {
+                            simulate(level, *seed, options.car, options.driver, options.seconds)
+                        })
+                        .collect::<Vec<_>>()
+                })
+            })
+            .collect();
+        workers
+            .into_iter()
+            .flat_map(|worker| worker.join().expect("simulation thread panicked"))
+            .collect()
+    })
+}
+
+fn percentile(sorted: &[u32], fraction: f32) -> u32 {
+    if sorted.is_empty() {
+        return 0;
+    }
+    sorted[((sorted.len() - 1) as f32 * fraction).round() as usize]
+}
+
+fn mean(values: impl Iterator<Item = f32>) -> f32 {
+    let (sum, count) = values.fold((0.0, 0), |(sum, count), value| (sum + value, count + 1));
+    if count == 0 {
+        0.0
+    } else {
+        sum / count as f32
+    }
+}
+
+fn summary(level: u8, reports: &[RunReport], driver: Driver) -> String {
+    let mut scores: Vec<u32> = reports.iter().map(|report| report.score).collect();
+    scores.sort_unstable();
+    let distance: f32 = reports.iter().map(|report| report.distance).sum();
+    let crashes: u32 = reports.iter().map(|report| report.crashes).sum();
+    format!(
+        concat!(
+            "{{\"level\":{},\"driver\":\"{}\",\"runs\":{},\"clear_rate\":{:.4},",
+            "\"mean_distance\":{:.1},\"mean_seconds\":{:.2},\"crash_rate\":{:.4},",
+            "\"score\":{{\"min\":{},\"p10\":{},\"p25\":{},\"p50\":{},\"p75\":{},\"p90\":{},\"max\":{},\"mean\":{:.1}}},",
+            "\"dead_ends\":{{\"total\":{},\"mean\":{:.3},\"max\":{}}}}}"
+        ),
+        level,
+        driver.name(),
+        reports.len(),
+        mean(reports.iter().map(|report| report.cleared as u8 as f32)),
+        mean(reports.iter().map(|report| report.distance)),
+        mean(reports.iter().map(|report| report.seconds)),
+        if distance > 0.0 {
+            crashes as f32 * 10_000.0 / distance
+        } else {
+            0.0
+        },
+        scores.first().copied().unwrap_or_default(),
+        percentile(&scores, 0.1),
+        percentile(&scores, 0.25),
+        percentile(&scores, 0.5),
+        percentile(&scores, 0.75),
+        percentile(&scores, 0.9),
+        scores.last().copied().unwrap_or_default(),
+        mean(scores.iter().map(|score| *score as f32)),
+        reports.iter().map(|report| report.dead_ends).sum::<u32>(),
+        mean(reports.iter().map(|report| report.dead_ends as f32)),
+        reports
+            .iter()
+            .map(|report| report.dead_ends)
+            .max()
+            .unwrap_or_default(),
+    )
+}
+
+fn main() {
+    let options = match parse(std::env::args().skip(1)) {
+        Ok(options) => options,
+        Err(error) => {
+            eprintln!("{}\n\n{}", error, USAGE);
+            process::exit(2);
+        }
+    };
+
+    if options.json {
+        let levels: Vec<String> = options
+            .levels
+            .iter()
+            .map(|level| summary(*level, &run_level(*level, &options), options.driver))
+            .collect();
+        println!("[\n  {}\n]", levels.join(",\n  "));
+        return;
+    }
+
+    println!("level,seed,driver,distance,seconds,crashes,crash_rate,score,cleared,dead_ends");
+    for level in options.levels.iter() {
+        for report in run_level(*level, &options) {
+            println!(
+                "{},{},{},{:.1},{:.2},{},{:.4},{},{},{}",
+                report.level,
+                report.seed,
+                options.driver.name(),
+                report.distance,
+                report.seconds,
+                report.crashes,
+                report.crash_rate(),
+                report.score,
+                report.cleared,
+                report.dead_ends,
+            );
+        }
+    }
+}
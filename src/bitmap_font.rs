@@ -0,0 +1,210 @@
+use bevy::prelude::*;
+
+/// Glyph images making up the HUD font, packed into one atlas once loaded.
+#[derive(Resource, Default)]
+pub struct BitmapFont {
+    glyphs: Vec<(char, Handle<Image>)>,
+    atlas: Option<FontAtlas>,
+}
+
+struct FontAtlas {
+    texture: Handle<Image>,
+    layout: Handle<TextureAtlasLayout>,
+    /// Atlas index and width over height for each glyph.
+    glyphs: Vec<(char, usize, f32)>,
+}
+
+impl FontAtlas {
+    fn glyph(&self, char: char) -> Option<(usize, f32)> {
+        self.glyphs
+            .iter()
+            .find(|(glyph, _, _)| *glyph == char)
+            .map(|(_, index, aspect)| (*index, *aspect))
+    }
+
+    /// Advance used for characters the font has no glyph for.
+    fn blank_aspect(&self) -> f32 {
+        self.glyphs.first().map_or(0.5, |(_, _, aspect)| *aspect)
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum TextAlign {
+    #[default]
+    Left,
+    Center,
+    Right,
+}
+
+/// A string drawn with `BitmapFont`. Put it on a node; the glyphs are kept as
+/// its children and only rebuilt when this component changes.
+#[derive(Component, Clone, Debug)]
+pub struct BitmapText {
+    pub value: String,
+    /// Glyph height as a percentage of the window height.
+    pub size: f32,
+    /// Extra space between glyphs as a fraction of `size`, negative to tighten.
+    pub kerning: f32,
+    pub align: TextAlign,
+    pub color: Color,
+}
+
+impl BitmapText {
+    pub fn new(value: impl Into<String>, size: f32) -> Self {
+        BitmapText {
+            value: value.into(),
+            size,
+            kerning: 0.0,
+            align: TextAlign::Left,
+            color: Color::WHITE,
+        }
+    }
+
+    pub fn with_align(mut self, align: TextAlign) -> Self {
+        self.align = align;
+        self
+    }
+
+    pub fn with_kerning(mut self, kerning: f32) -> Self {
+        self.kerning = kerning;
+        self
+    }
+
+    /// Replaces the string. Through a `Mut` this always counts as a change and
+    /// rebuilds the glyphs, so callers only set a value that differs.
+    pub fn set_value(&mut self, value: impl Into<String>) {
+        self.value = value.into();
+    }
+}
+
+#[derive(Component)]
+pub struct BitmapGlyph;
+
+pub fn load_bitmap_font(mut commands: Commands, asset_server: Res<AssetServer>) {
+    let mut glyphs: Vec<(char, Handle<Image>)> = (0..10)
+        .map(|n| {
+            (
+                char::from_digit(n, 10).unwrap(),
+                asset_server.load((1687 + n).to_string() + ".png"),
+            )
+        })
+        .collect();
+    glyphs.push(('/', asset_server.load("slash.png")));
+    commands.insert_resource(BitmapFont {
+        glyphs,
+        atlas: None,
+    });
+}
+
+pub fn build_bitmap_font(
+    mut font: ResMut<BitmapFont>,
+    mut images: ResMut<Assets<Image>>,
+    mut layouts: ResMut<Assets<TextureAtlasLayout>>,
+) {
+    if font.atlas.is_some() || !font.glyphs.iter().all(|(_, image)| images.contains(image)) {
+        return;
+    }
+
+    let mut builder = TextureAtlasBuilder::default();
+    for (_, image) in font.glyphs.iter() {
+        if let Some(glyph) = images.get(image) {
+            builder.add_texture(Some(image.id()), glyph);
+        }
+    }
+    let Ok((layout, texture)) = builder.build() else {
+        return;
+    };
+    let glyphs = font
+        .glyphs
+        .iter()
+        .filter_map(|(char, image)| {
+            let index = layout.get_texture_index(image)?;
+            let size = layout.textures[index].size().as_vec2();
+            Some((*char, index, size.x / size.y))
+        })
+        .collect();
+
+    font.atlas = Some(FontAtlas {
+        texture: images.add(texture),
+        layout: layouts.add(layout),
+        glyphs,
+    });
+}
+
+fn glyph_style(text: &BitmapText, aspect: f32, first: bool) -> Style {
+    Style {
+        width: Val::Vh(text.size * aspect),
+        height: Val::Vh(text.size),
+        margin: UiRect::left(Val::Vh(if first { 0.0 } else { text.size * text.kerning })),
+        flex_shrink: 0.0,
+        ..default()
+    }
+}
+
+type GlyphOnly = (With<BitmapGlyph>, Without<BitmapText>);
+
+pub fn render_bitmap_text(
+    mut commands: Commands,
+    font: Res<BitmapFont>,
+    mut texts: Query<(Entity, Ref<BitmapText>, &mut Style, Option<&Children>)>,
+    mut glyphs: Query<(&mut TextureAtlas, &mut UiImage, &mut Style), GlyphOnly>,
+) {
+    let Some(atlas) = &font.atlas else {
+        return;
+    };
+
+    for (entity, text, mut style, children) in texts.iter_mut() {
+        if !text.is_changed() && !font.is_changed() {
+            continue;
+        }
+        style.flex_direction = FlexDirection::Row;
+        style.justify_content = match text.align {
+            TextAlign::Left => JustifyContent::FlexStart,
+            TextAlign::Center => JustifyContent::Center,
+            TextAlign::Right => JustifyContent::FlexEnd,
+        };
+
+        let layout: Vec<(usize, f32, Color)> = text
+            .value
+            .chars()
+            .map(|char| match atlas.glyph(char) {
+                Some((index, aspect)) => (index, aspect, text.color),
+                None => (0, atlas.blank_aspect(), Color::NONE),
+            })
+            .collect();
+        let children: Vec<Entity> = children.map_or(Vec::new(), |children| children.to_vec());
+
+        // Same length: retarget the existing glyphs, otherwise rebuild the row.
+        if children.len() == layout.len() {
+            for (n, (child, (index, aspect, color))) in children.iter().zip(layout).enumerate() {
+                if let Ok((mut glyph, mut image, mut glyph_layout)) = glyphs.get_mut(*child) {
+                    glyph.index = index;
+                    image.color = color;
+                    *glyph_layout = glyph_style(&text, aspect, n == 0);
+                }
+            }
+            continue;
+        }
+        commands.entity(entity).despawn_descendants();
+        commands.entity(entity).with_children(|row| {
+            for (n, (index, aspect, color)) in layout.into_iter().enumerate() {
+                row.spawn((
+                    ImageBundle {
+                        style: glyph_style(&text, aspect, n == 0),
+                        image: UiImage {
+                            texture: atlas.texture.clone(),
+                            color,
+                            ..default()
+                        },
+                        ..default()
+                    },
+                    TextureAtlas {
+                        layout: atlas.layout.clone(),
+                        index,
+                    },
+                    BitmapGlyph,
+                ));
+            }
+        });
+    }
+}
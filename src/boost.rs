@@ -0,0 +1,130 @@
+use bevy::prelude::*;
+
+use crate::{Car, CarState, Hud, Obstacle};
+
+pub const BOOST_SPEED_FACTOR: f32 = 1.35;
+pub const BOOST_ACCELERATION: f32 = 400.0;
+pub const BOOST_DECAY: f32 = 150.0;
+pub const BOOST_ZOOM: f32 = 0.92;
+
+const CLEAN_FILL_RATE: f32 = 0.04;
+const NEAR_MISS_BONUS: f32 = 0.1;
+const NEAR_MISS_MARGIN: f32 = 40.0;
+const DRAIN_RATE: f32 = 0.35;
+
+#[derive(Resource, Default)]
+pub struct Boost {
+    pub meter: f32,
+    pub active: bool,
+}
+
+/// Marks obstacles the car has already driven past so near misses count once.
+#[derive(Component)]
+pub struct Passed;
+
+pub fn reset_boost(mut boost: ResMut<Boost>) {
+    *boost = Boost::default();
+}
+
+/// Obstacles the car hasn't been credited with passing yet.
+type Unpassed = (Without<Car>, Without<Passed>);
+
+pub fn update_boost(
+    mut commands: Commands,
+    button_input: Res<ButtonInput<KeyCode>>,
+    time: Res<Time>,
+    car: Query<(&Car, &Transform), Without<Obstacle>>,
+    obstacles: Query<(Entity, &Obstacle, &Transform), Unpassed>,
+    mut boost: ResMut<Boost>,
+) {
+    if car.is_empty() {
+        return;
+    }
+    let (car, car_transform) = car.single();
+    let car_pos = car_transform.translation;
+    let car_size = car.stats.size();
+
+    if car.state == CarState::Crashed {
+        boost.active = false;
+        return;
+    }
+
+    for (obstacle_entity, obstacle, obstacle_transform) in obstacles.iter() {
+        let passed_x = car_pos.x - (car_size.x + obstacle.size.x) / 2.0;
+        if obstacle_transform.translation.x > passed_x {
+            continue;
+        }
+        commands.entity(obstacle_entity).insert(Passed);
+        if (obstacle_transform.translation.y - car_pos.y).abs()
+            <= (car_size.y + obstacle.size.y) / 2.0 + NEAR_MISS_MARGIN
+        {
+            boost.meter += NEAR_MISS_BONUS;
+        }
+    }
+
+    let wants_boost = button_input.pressed(KeyCode::Space)
+        || button_input.pressed(KeyCode::ShiftLeft)
+        || button_input.pressed(KeyCode::ShiftRight);
+    boost.active = wants_boost && boost.meter > 0.0;
+
+    if boost.active {
+        boost.meter -= DRAIN_RATE * time.delta_seconds();
+    } else {
+        boost.meter += CLEAN_FILL_RATE * time.delta_seconds();
+    }
+    boost.meter = boost.meter.clamp(0.0, 1.0);
+}
+
+#[derive(Component)]
+pub struct BoostGauge;
+
+pub fn spawn_boost_gauge(mut commands: Commands) {
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    top: Val::Percent(17.0),
+                    left: Val::Percent(10.0 / 1920.0 * 100.0),
+                    width: Val::Percent(12.0),
+                    height: Val::Percent(2.0),
+                    border: UiRect::all(Val::Px(2.0)),
+                    ..default()
+                },
+                border_color: BorderColor(Color::WHITE),
+                background_color: BackgroundColor(Color::srgba(0.0, 0.0, 0.0, 0.5)),
+                ..default()
+            },
+            Hud,
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                NodeBundle {
+                    style: Style {
+                        width: Val::Percent(0.0),
+                        height: Val::Percent(100.0),
+                        ..default()
+                    },
+                    background_color: BackgroundColor(Color::srgb(0.2, 0.8, 1.0)),
+                    ..default()
+                },
+                BoostGauge,
+            ));
+        });
+}
+
+pub fn update_boost_gauge(
+    boost: Res<Boost>,
+    mut gauge: Query<(&mut Style, &mut BackgroundColor), With<BoostGauge>>,
+) {
+    if !boost.is_changed() {
+        return;
+    }
+    let (mut style, mut color) = gauge.single_mut();
+    style.width = Val::Percent(boost.meter * 100.0);
+    color.0 = if boost.active {
+        Color::srgb(1.0, 0.5, 0.1)
+    } else {
+        Color::srgb(0.2, 0.8, 1.0)
+    };
+}
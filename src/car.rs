@@ -1,22 +1,68 @@
 use std::f32::consts::PI;
 use std::time::Duration;
 
-use crate::{CameraMarker, LevelAssets, Score, LevelAssetMarker};
+use crate::{
+    Boost, CameraMarker, CarStats, LevelAssetMarker, LevelAssets, MinimapMarker, PowerUps, Score,
+    SelectedCar, SheetId, SpriteAnimator, BOOST_ACCELERATION, BOOST_DECAY, BOOST_SPEED_FACTOR,
+    DEFAULT_STATS, MAX_HEALTH, WORLD_HEIGHT, WORLD_WIDTH,
+};
 use bevy::prelude::*;
 use bevy::time::Timer;
 
-const CARHEIGHT: f32 = 105.0;
-const CARWIDTH: f32 = 135.0;
-const YSPEED: f32 = 500.0;
-
 #[derive(Component)]
 pub struct Car {
     pub speed: Vec2,
     pub state: CarState,
-    sprite_index: usize,
     frame_timer: Timer,
     pub position: Vec3,
-    pub collision_counter: u8,
+    pub health: f32,
+    pub invulnerability: Option<Timer>,
+    pub knockback_y: Option<f32>,
+    pub stats: CarStats,
+}
+
+impl Default for Car {
+    fn default() -> Self {
+        Car {
+            speed: Vec2 {
+                x: 0.0,
+                y: DEFAULT_STATS.lateral_speed,
+            },
+            state: CarState::Moving,
+            frame_timer: Timer::new(Duration::from_secs_f32(1.0), TimerMode::Repeating),
+            position: Vec3 {
+                x: 0.0,
+                y: 0.0,
+                z: 1.0,
+            },
+            health: MAX_HEALTH,
+            invulnerability: None,
+            knockback_y: None,
+            stats: DEFAULT_STATS,
+        }
+    }
+}
+
+impl Car {
+    pub fn can_crash(&self) -> bool {
+        self.state == CarState::Moving && self.invulnerability.is_none()
+    }
+}
+
+/// Tuning for how the car recovers after a crash.
+#[derive(Resource)]
+pub struct CrashRecovery {
+    pub invulnerable_seconds: f32,
+    pub knockback_speed: f32,
+}
+
+impl Default for CrashRecovery {
+    fn default() -> Self {
+        CrashRecovery {
+            invulnerable_seconds: 2.5,
+            knockback_speed: 600.0,
+        }
+    }
 }
 
 #[derive(PartialEq)]
@@ -27,23 +73,20 @@ pub enum CarState {
 
 pub fn spawn_car(
     mut commands: Commands,
-    window: Query<&Window>,
     level_assets: ResMut<LevelAssets>,
+    selected: Res<SelectedCar>,
     car: Query<&Car>,
 ) {
     if !car.is_empty() {
         return;
     }
 
-    let window_scale = 1080.0 / window.single().height();
+    let stats = selected.car().stats;
 
     commands.spawn((
         SpriteBundle {
             sprite: Sprite {
-                custom_size: Some(Vec2 {
-                    x: CARWIDTH / window_scale,
-                    y: CARHEIGHT / window_scale,
-                }),
+                custom_size: Some(stats.size()),
                 ..default()
             },
             texture: level_assets.car_texture.clone(),
@@ -51,78 +94,93 @@ pub fn spawn_car(
             ..default()
         },
         Car {
-            speed: Vec2 { x: 0.0, y: YSPEED },
-            state: CarState::Moving,
-            frame_timer: Timer::new(Duration::from_secs_f32(1.0), TimerMode::Repeating),
-            sprite_index: 0,
-            position: Vec3 {
+            speed: Vec2 {
                 x: 0.0,
-                y: 0.0,
-                z: 1.0,
+                y: stats.lateral_speed,
             },
-            collision_counter: 0,
+            stats,
+            ..default()
+        },
+        SpriteAnimator::new(SheetId::Car),
+        MinimapMarker {
+            color: Color::srgb(0.2, 0.9, 0.3),
         },
-        LevelAssetMarker
+        LevelAssetMarker,
     ));
 }
 
+#[allow(clippy::too_many_arguments)]
 pub fn update_car(
     button_input: Res<ButtonInput<KeyCode>>,
     time: Res<Time>,
     mut cars: Query<(&mut Car, &mut Transform), Without<CameraMarker>>,
     camera: Query<&Transform, With<CameraMarker>>,
-    window: Query<&Window>,
     mut score: ResMut<Score>,
+    power_ups: Res<PowerUps>,
+    boost: Res<Boost>,
+    recovery: Res<CrashRecovery>,
 ) {
-    let width = window.single().width();
-    let height = window.single().height();
-    let window_scale = 1080.0 / height;
-
     for (mut car, mut transform) in cars.iter_mut() {
+        let stats = car.stats;
         match car.state {
             CarState::Moving => {
                 if button_input.pressed(KeyCode::KeyW) || button_input.pressed(KeyCode::ArrowUp) {
                     car.position.y += car.speed.y * time.delta_seconds();
                     car.position.y = car.position.y.clamp(
-                        -height / 2.0 + CARHEIGHT / window_scale / 2.0,
-                        height / 2.0 - CARHEIGHT / window_scale / 2.0,
+                        -WORLD_HEIGHT / 2.0 + stats.height / 2.0,
+                        WORLD_HEIGHT / 2.0 - stats.height / 2.0,
                     );
                 }
                 if button_input.pressed(KeyCode::KeyS) || button_input.pressed(KeyCode::ArrowDown) {
                     car.position.y -= car.speed.y * time.delta_seconds();
                     car.position.y = car.position.y.clamp(
-                        -height / 2.0 + CARHEIGHT / window_scale / 2.0,
-                        height / 2.0 - CARHEIGHT / window_scale / 2.0,
+                        -WORLD_HEIGHT / 2.0 + stats.height / 2.0,
+                        WORLD_HEIGHT / 2.0 - stats.height / 2.0,
                     );
                 }
                 if button_input.pressed(KeyCode::KeyD) || button_input.pressed(KeyCode::ArrowRight)
                 {
                     car.position.x += car.speed.y * time.delta_seconds();
                     car.position.x = car.position.x.clamp(
-                        camera.single().translation.x - width / 4.0,
-                        camera.single().translation.x + width / 4.0,
+                        camera.single().translation.x - WORLD_WIDTH / 4.0,
+                        camera.single().translation.x + WORLD_WIDTH / 4.0,
                     );
                 }
                 if button_input.pressed(KeyCode::KeyA) || button_input.pressed(KeyCode::ArrowLeft) {
                     car.position.x -= car.speed.y * time.delta_seconds();
                     car.position.x = car.position.x.clamp(
-                        camera.single().translation.x - width / 4.0,
-                        camera.single().translation.x + width / 4.0,
+                        camera.single().translation.x - WORLD_WIDTH / 4.0,
+                        camera.single().translation.x + WORLD_WIDTH / 4.0,
                     );
                 }
 
                 car.position.x += car.speed.x * time.delta_seconds();
                 transform.translation = car.position;
-                if car.speed.x < 1100.00 {
-                    car.speed.x += 75.0 * time.delta_seconds();
+                let (cap, acceleration) = if boost.active {
+                    (stats.max_speed * BOOST_SPEED_FACTOR, BOOST_ACCELERATION)
+                } else {
+                    (stats.max_speed, stats.acceleration)
+                };
+                if car.speed.x < cap {
+                    car.speed.x = (car.speed.x + acceleration * time.delta_seconds()).min(cap);
+                } else {
+                    car.speed.x = (car.speed.x - BOOST_DECAY * time.delta_seconds()).max(cap);
                 }
                 if car.speed.x > 500.0 {
-                    score.score += 10.0 * time.delta_seconds();
+                    score.score += 10.0 * power_ups.score_multiplier() * time.delta_seconds();
                 }
             }
             CarState::Crashed => {
                 car.speed.x = 100.0;
                 car.position.x += car.speed.x * time.delta_seconds();
+                if let Some(target) = car.knockback_y {
+                    let step = recovery.knockback_speed * time.delta_seconds();
+                    let offset = target - car.position.y;
+                    car.position.y += offset.clamp(-step, step);
+                    if offset.abs() <= step {
+                        car.knockback_y = None;
+                    }
+                }
                 transform.translation = car.position;
 
                 if car.frame_timer.paused() {
@@ -140,7 +198,6 @@ pub fn update_car(
                 ));
 
                 if car.frame_timer.just_finished() {
-                    car.sprite_index = 0;
                     car.state = CarState::Moving;
                     transform.rotation = Quat::from_axis_angle(
                         Vec3 {
@@ -155,3 +212,19 @@ pub fn update_car(
         }
     }
 }
+
+pub fn update_invulnerability(time: Res<Time>, mut cars: Query<(&mut Car, &mut Sprite)>) {
+    for (mut car, mut sprite) in cars.iter_mut() {
+        let Some(timer) = car.invulnerability.as_mut() else {
+            continue;
+        };
+        timer.tick(time.delta());
+        if timer.finished() {
+            car.invulnerability = None;
+            sprite.color.set_alpha(1.0);
+        } else {
+            let visible = ((timer.elapsed_secs() / 0.1) as u32) % 2 == 0;
+            sprite.color.set_alpha(if visible { 1.0 } else { 0.25 });
+        }
+    }
+}
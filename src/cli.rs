@@ -0,0 +1,207 @@
+use std::path::PathBuf;
+use std::process;
+
+use bevy::prelude::*;
+use bevy::window::WindowMode;
+
+use crate::{GameState, Recorder, Recording, Replay, LEVELS};
+
+pub const USAGE: &str = "\
+usage: midnight_motorist [options]
+
+  --level <n>             start straight on level n, skipping the menus
+  --seed <n>              seed obstacles and pickups for a repeatable run
+  --resolution <w>x<h>    window size, 1280x720 by default
+  --fullscreen            start in borderless fullscreen
+  --mute                  turn all audio off
+  --replay <file>         race a ghost from a recording; sets level, seed and car
+  --record <file>         write the drive to a file whenever the race stops
+  --debug                 start with the debug overlay on (dev-tools builds)
+  --help                  show this message";
+
+#[derive(Debug, Default, PartialEq)]
+pub struct CliArgs {
+    pub level: Option<u8>,
+    pub seed: Option<u64>,
+    pub resolution: Option<(f32, f32)>,
+    pub fullscreen: bool,
+    pub mute: bool,
+    pub replay: Option<PathBuf>,
+    pub record: Option<PathBuf>,
+    pub debug: bool,
+    pub help: bool,
+}
+
+fn next_value(name: &str, args: &mut impl Iterator<Item = String>) -> Result<String, String> {
+    args.next().ok_or_else(|| format!("{} needs a value", name))
+}
+
+fn number<T: std::str::FromStr>(name: &str, value: &str) -> Result<T, String> {
+    value
+        .parse()
+        .map_err(|_| format!("invalid {} '{}'", name, value))
+}
+
+impl CliArgs {
+    pub fn parse(args: impl IntoIterator<Item = String>) -> Result<Self, String> {
+        let mut parsed = CliArgs::default();
+        let mut args = args.into_iter();
+        while let Some(arg) = args.next() {
+            // Accept `--flag=value` as well as `--flag value`.
+            let (name, inline) = match arg.split_once('=') {
+                Some((name, value)) => (name.to_string(), Some(value.to_string())),
+                None => (arg, None),
+            };
+            let mut value = |name: &str| match inline.clone() {
+                Some(value) => Ok(value),
+                None => next_value(name, &mut args),
+            };
+            match name.as_str() {
+                "--level" => {
+                    let level: u8 = number("level", &value("--level")?)?;
+                    if !(1..=LEVELS.len() as u8).contains(&level) {
+                        return Err(format!("level must be 1 to {}", LEVELS.len()));
+                    }
+                    parsed.level = Some(level);
+                }
+                "--seed" => parsed.seed = Some(number("seed", &value("--seed")?)?),
+                "--resolution" => {
+                    let resolution = value("--resolution")?;
+                    let (width, height) = resolution
+                        .split_once('x')
+                        .ok_or_else(|| format!("invalid resolution '{}'", resolution))?;
+                    let (width, height): (f32, f32) =
+                        (number("resolution", width)?, number("resolution", height)?);
+                    if !(width > 0.0 && height > 0.0) {
+                        return Err(format!("invalid resolution '{}'", resolution));
+                    }
+                    parsed.resolution = Some((width, height));
+                }
+                "--fullscreen" => parsed.fullscreen = true,
+                "--mute" => parsed.mute = true,
+                "--replay" => parsed.replay = Some(value("--replay")?.into()),
+                "--record" => parsed.record = Some(value("--record")?.into()),
+                "--debug" => parsed.debug = true,
+                "--help" | "-h" => parsed.help = true,
+                _ => return Err(format!("unknown option '{}'", name)),
+            }
+        }
+        Ok(parsed)
+    }
+
+    /// Parses the process arguments, printing usage and exiting when they are
+    /// wrong or `--help` is given.
+    pub fn from_env() -> Self {
+        let args = match CliArgs::parse(std::env::args().skip(1)) {
+            Ok(args) => args,
+            Err(error) => {
+                eprintln!("{}\n\n{}", error, USAGE);
+                process::exit(2);
+            }
+        };
+        if args.help {
+            println!("{}", USAGE);
+            process::exit(0);
+        }
+        if args.debug && !cfg!(feature = "dev-tools") {
+            eprintln!("--debug needs a build with the dev-tools feature, ignoring it");
+        }
+        args
+    }
+
+    pub fn window(&self) -> Window {
+        Window {
+            title: "Midnight Motorist".into(),
+            name: Some("Midnight Motorist".into()),
+            resolution: self.resolution.unwrap_or((1280.0, 720.0)).into(),
+            mode: if self.fullscreen {
+                WindowMode::BorderlessFullscreen
+            } else {
+                WindowMode::Windowed
+            },
+            ..default()
+        }
+    }
+
+    pub fn volume(&self) -> GlobalVolume {
+        GlobalVolume::new(if self.mute { 0.0 } else { 1.0 })
+    }
+
+    /// Loads `--replay`, exiting if it can't be read.
+    pub fn load_replay(&self) -> Option<Replay> {
+        let path = self.replay.as_ref()?;
+        match Recording::load(path) {
+            Ok(recording) => Some(Replay { recording }),
+            Err(error) => {
+                eprintln!("{}", error);
+                process::exit(1);
+            }
+        }
+    }
+
+    /// Seed for the run. A replay brings its own, and recording without one
+    /// picks one so the recording can be replayed.
+    pub fn seed(&self, replay: Option<&Replay>) -> Option<u64> {
+        replay
+            .map(|replay| replay.recording.seed)
+            .or(self.seed)
+            .or_else(|| self.record.is_some().then(rand::random))
+    }
+
+    pub fn recorder(&self, seed: Option<u64>) -> Option<Recorder> {
+        let path = self.record.clone()?;
+        Some(Recorder::new(path, seed.unwrap_or_default()))
+    }
+
+    /// Jumping to a level or replay skips the garage and level select.
+    pub fn start_state(&self) -> GameState {
+        if self.level.is_some() || self.replay.is_some() {
+            GameState::LoadNextLevel
+        } else {
+            GameState::Garage
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &[&str]) -> Result<CliArgs, String> {
+        CliArgs::parse(args.iter().map(|arg| arg.to_string()))
+    }
+
+    #[test]
+    fn parses_a_qa_scenario() {
+        let args = parse(&[
+            "--level",
+            "2",
+            "--seed=42",
+            "--resolution",
+            "1920x1080",
+            "--mute",
+            "--record",
+            "run.rec",
+        ])
+        .unwrap();
+        assert_eq!(
+            args,
+            CliArgs {
+                level: Some(2),
+                seed: Some(42),
+                resolution: Some((1920.0, 1080.0)),
+                mute: true,
+                record: Some("run.rec".into()),
+                ..default()
+            }
+        );
+        assert_eq!(args.start_state(), GameState::LoadNextLevel);
+
+        assert!(parse(&["--level", "9"]).is_err());
+        assert!(parse(&["--resolution", "wide"]).is_err());
+        assert!(parse(&["--resolution", "0x720"]).is_err());
+        assert!(parse(&["--resolution", "1280x-720"]).is_err());
+        assert!(parse(&["--seed"]).is_err());
+        assert!(parse(&["--turbo"]).is_err());
+    }
+}
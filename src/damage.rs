@@ -0,0 +1,288 @@
+use bevy::prelude::*;
+
+use crate::{Car, Hud, LevelAssets, ObstacleKind, SelectedCar};
+
+pub const MAX_HEALTH: f32 = 100.0;
+pub const REPAIR_AMOUNT: f32 = 35.0;
+const BOOST_DAMAGE_FACTOR: f32 = 1.5;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DamageState {
+    Intact,
+    Dented,
+    Wrecked,
+}
+
+impl DamageState {
+    pub fn from_health(health: f32) -> Self {
+        if health > MAX_HEALTH * 2.0 / 3.0 {
+            DamageState::Intact
+        } else if health > MAX_HEALTH / 3.0 {
+            DamageState::Dented
+        } else {
+            DamageState::Wrecked
+        }
+    }
+
+    /// Fraction of the car's lateral speed it can still steer with.
+    pub fn handling(&self) -> f32 {
+        match self {
+            DamageState::Intact => 1.0,
+            DamageState::Dented => 0.85,
+            DamageState::Wrecked => 0.65,
+        }
+    }
+
+    fn tint(&self) -> Color {
+        match self {
+            DamageState::Intact => Color::WHITE,
+            DamageState::Dented => Color::srgb(0.85, 0.75, 0.75),
+            DamageState::Wrecked => Color::srgb(0.6, 0.4, 0.4),
+        }
+    }
+}
+
+impl ObstacleKind {
+    pub fn damage(&self) -> f32 {
+        match self {
+            ObstacleKind::Cruiser => 20.0,
+            ObstacleKind::LaneChanger => 20.0,
+            ObstacleKind::BrakingTruck => 30.0,
+            ObstacleKind::Roadblock => 35.0,
+            ObstacleKind::Oscillator => 15.0,
+        }
+    }
+}
+
+/// Damage dealt by hitting `kind` at `speed`: half at a standstill, one and a
+/// half times at `max_speed`, and more again when boosting.
+pub fn crash_damage(kind: ObstacleKind, speed: f32, max_speed: f32, boosting: bool) -> f32 {
+    let mut damage = kind.damage() * (0.5 + speed / max_speed);
+    if boosting {
+        damage *= BOOST_DAMAGE_FACTOR;
+    }
+    damage
+}
+
+/// Applies the handling and tint of the car's damage state, only writing them
+/// when they change so `Changed<Car>` keeps meaning something. The tint keeps
+/// the sprite's alpha, which `update_invulnerability` blinks.
+pub fn update_damage_state(mut car: Query<(&mut Car, &mut Sprite)>) {
+    for (mut car, mut sprite) in car.iter_mut() {
+        let state = DamageState::from_health(car.health);
+        let lateral_speed = car.stats.lateral_speed * state.handling();
+        if car.speed.y != lateral_speed {
+            car.speed.y = lateral_speed;
+        }
+        let tint = state.tint().with_alpha(sprite.color.alpha());
+        if sprite.color != tint {
+            sprite.color = tint;
+        }
+    }
+}
+
+#[derive(Component)]
+pub struct HealthBar;
+
+pub fn spawn_health_bar(mut commands: Commands) {
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    top: Val::Percent(20.5),
+                    left: Val::Percent(10.0 / 1920.0 * 100.0),
+                    width: Val::Percent(12.0),
+                    height: Val::Percent(2.0),
+                    border: UiRect::all(Val::Px(2.0)),
+                    ..default()
+                },
+                border_color: BorderColor(Color::WHITE),
+                background_color: BackgroundColor(Color::srgba(0.0, 0.0, 0.0, 0.5)),
+                ..default()
+            },
+            Hud,
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                NodeBundle {
+                    style: Style {
+                        width: Val::Percent(100.0),
+                        height: Val::Percent(100.0),
+                        ..default()
+                    },
+                    background_color: BackgroundColor(Color::srgb(0.2, 0.9, 0.3)),
+                    ..default()
+                },
+                HealthBar,
+            ));
+        });
+}
+
+pub fn update_health_bar(
+    car: Query<&Car, Changed<Car>>,
+    mut bar: Query<(&mut Style, &mut BackgroundColor), With<HealthBar>>,
+) {
+    let Ok(car) = car.get_single() else {
+        return;
+    };
+    let (mut style, mut color) = bar.single_mut();
+    style.width = Val::Percent(car.health / MAX_HEALTH * 100.0);
+    color.0 = match DamageState::from_health(car.health) {
+        DamageState::Intact => Color::srgb(0.2, 0.9, 0.3),
+        DamageState::Dented => Color::srgb(1.0, 0.8, 0.2),
+        DamageState::Wrecked => Color::srgb(0.9, 0.2, 0.2),
+    };
+}
+
+const LIFE_ICON_HEIGHT: f32 = 3.0;
+const LIFE_FLASH_SECONDS: f32 = 0.6;
+const LIFE_LOST_COLOR: Srgba = Srgba::new(0.3, 0.3, 0.3, 0.5);
+
+/// How much of its share of health icon `index` of `lives` still holds with
+/// `health` left, from 1 for a whole icon down to 0 once the share is gone.
+pub fn life_fill(health: f32, lives: u32, index: u32) -> f32 {
+    let share = MAX_HEALTH / lives.max(1) as f32;
+    (health / share - index as f32).clamp(0.0, 1.0)
+}
+
+/// Lit for a whole share of health, fading out as the share is used up.
+fn life_color(fill: f32) -> Color {
+    LIFE_LOST_COLOR.mix(&Srgba::WHITE, fill).into()
+}
+
+/// Row of car icons showing the car's health in `LevelAssets::lives` shares.
+#[derive(Component)]
+pub struct Lives {
+    /// Health the icons were last drawn for.
+    health: f32,
+}
+
+#[derive(Component)]
+pub struct LifeIcon {
+    index: u32,
+    flash: Option<Timer>,
+}
+
+pub fn spawn_lives(mut commands: Commands) {
+    commands.spawn((
+        NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                top: Val::Percent(24.0),
+                left: Val::Percent(10.0 / 1920.0 * 100.0),
+                column_gap: Val::Vh(0.5),
+                ..default()
+            },
+            ..default()
+        },
+        Lives { health: MAX_HEALTH },
+        Hud,
+    ));
+}
+
+/// Rebuilds the icons when the level or car changes, and shakes and flashes
+/// every icon a crash takes health from.
+pub fn update_lives(
+    mut commands: Commands,
+    time: Res<Time>,
+    level_assets: Res<LevelAssets>,
+    selected: Res<SelectedCar>,
+    car: Query<&Car>,
+    mut lives: Query<(Entity, &mut Lives)>,
+    mut icons: Query<(&mut LifeIcon, &mut UiImage, &mut Style), Without<Lives>>,
+) {
+    let (entity, mut lives) = lives.single_mut();
+    let count = level_assets.lives;
+    let health = car.get_single().map_or(MAX_HEALTH, |car| car.health);
+
+    if level_assets.is_changed() || selected.is_changed() {
+        let size = selected.car().stats.size();
+        commands.entity(entity).despawn_descendants();
+        commands.entity(entity).with_children(|row| {
+            for index in 0..count {
+                row.spawn((
+                    ImageBundle {
+                        style: Style {
+                            width: Val::Vh(LIFE_ICON_HEIGHT * size.x / size.y),
+                            height: Val::Vh(LIFE_ICON_HEIGHT),
+                            ..default()
+                        },
+                        image: UiImage {
+                            texture: level_assets.car_texture.clone(),
+                            color: life_color(life_fill(health, count, index)),
+                            ..default()
+                        },
+                        ..default()
+                    },
+                    LifeIcon { index, flash: None },
+                ));
+            }
+        });
+        lives.health = health;
+        return;
+    }
+
+    let previous = lives.health;
+    lives.health = health;
+    for (mut icon, mut image, mut style) in icons.iter_mut() {
+        let fill = life_fill(health, count, icon.index);
+        if fill < life_fill(previous, count, icon.index) {
+            icon.flash = Some(Timer::from_seconds(LIFE_FLASH_SECONDS, TimerMode::Once));
+        }
+        let Some(flash) = icon.flash.as_mut() else {
+            image.color = life_color(fill);
+            continue;
+        };
+        if flash.tick(time.delta()).finished() {
+            icon.flash = None;
+            style.left = Val::Px(0.0);
+            continue;
+        }
+        let elapsed = flash.elapsed_secs();
+        let fade = flash.fraction_remaining();
+        style.left = Val::Px((elapsed * 60.0).sin() * 6.0 * fade);
+        image.color = if ((elapsed / 0.1) as u32) % 2 == 0 {
+            Color::srgb(1.0, 0.2, 0.2)
+        } else {
+            Color::WHITE
+        };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn steady_damage_state_leaves_the_car_unchanged() {
+        let mut app = App::new();
+        app.add_systems(Update, update_damage_state);
+        let mut car = Car::default();
+        car.health = MAX_HEALTH / 2.0;
+        let car = app.world_mut().spawn((car, Sprite::default())).id();
+        let last_changed = |app: &App| {
+            let entity = app.world().entity(car);
+            let car = entity.get_ref::<Car>().unwrap().last_changed();
+            let sprite = entity.get_ref::<Sprite>().unwrap().last_changed();
+            (car, sprite)
+        };
+
+        app.update();
+        let first = last_changed(&app);
+        app.update();
+        assert_eq!(last_changed(&app), first);
+        let sprite = app.world().get::<Sprite>(car).unwrap();
+        assert_eq!(sprite.color, DamageState::Dented.tint());
+    }
+
+    #[test]
+    fn each_life_holds_an_equal_share_of_health() {
+        assert_eq!(life_fill(MAX_HEALTH, 4, 3), 1.0);
+        assert_eq!(life_fill(87.5, 4, 3), 0.5);
+        assert_eq!(life_fill(87.5, 4, 2), 1.0);
+        assert_eq!(life_fill(50.0, 4, 2), 0.0);
+        assert_eq!(life_fill(0.5, 5, 0), 0.025);
+        assert_eq!(life_fill(-12.0, 5, 0), 0.0);
+    }
+}
@@ -0,0 +1,162 @@
+use bevy::color::palettes::css;
+use bevy::prelude::*;
+use bevy_inspector_egui::quick::WorldInspectorPlugin;
+use bevy_screen_diagnostics::{
+    ScreenDiagnostics, ScreenDiagnosticsPlugin, ScreenFrameDiagnosticsPlugin,
+};
+
+use crate::{
+    lane_y, CameraMarker, Car, LevelAssets, Obstacle, ObstacleSpawner, LANES, WORLD_HEIGHT,
+    WORLD_WIDTH,
+};
+
+/// Diagnostics added by `ScreenFrameDiagnosticsPlugin`.
+const SCREEN_DIAGNOSTICS: [&str; 2] = ["fps", "ms/frame"];
+
+/// Developer tooling, only built with the `dev-tools` feature.
+pub struct DevToolsPlugin;
+
+impl Plugin for DevToolsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_plugins(ScreenDiagnosticsPlugin::default())
+            .add_plugins(ScreenFrameDiagnosticsPlugin)
+            .add_plugins(WorldInspectorPlugin::new().run_if(debug_enabled))
+            .init_resource::<DebugOverlay>()
+            .add_systems(
+                Update,
+                (
+                    toggle_debug_overlay,
+                    sync_screen_diagnostics,
+                    draw_debug_overlay.run_if(debug_enabled),
+                )
+                    .chain(),
+            );
+    }
+}
+
+/// Developer overlay: hitboxes, lanes and spawn bounds drawn with gizmos, plus
+/// the world inspector and frame diagnostics. Toggled with F3 or `--debug`.
+#[derive(Resource, Default)]
+pub struct DebugOverlay {
+    pub enabled: bool,
+}
+
+pub fn debug_enabled(overlay: Res<DebugOverlay>) -> bool {
+    overlay.enabled
+}
+
+pub fn toggle_debug_overlay(
+    button_input: Res<ButtonInput<KeyCode>>,
+    mut overlay: ResMut<DebugOverlay>,
+) {
+    if button_input.just_pressed(KeyCode::F3) {
+        overlay.enabled = !overlay.enabled;
+    }
+}
+
+/// Screen diagnostics start out shown, so `hidden` tracks which way they were
+/// last toggled.
+pub fn sync_screen_diagnostics(
+    overlay: Res<DebugOverlay>,
+    mut diagnostics: ResMut<ScreenDiagnostics>,
+    mut hidden: Local<bool>,
+) {
+    if overlay.enabled != *hidden {
+        return;
+    }
+    for name in SCREEN_DIAGNOSTICS {
+        diagnostics.modify(name).toggle();
+    }
+    *hidden = !*hidden;
+}
+
+fn vertical_line(gizmos: &mut Gizmos, x: f32, color: impl Into<Color>) {
+    gizmos.line_2d(
+        Vec2::new(x, -WORLD_HEIGHT / 2.0),
+        Vec2::new(x, WORLD_HEIGHT / 2.0),
+        color,
+    );
+}
+
+/// Line at `x` if it is in `view`, otherwise an arrow at height `y` pinned to
+/// the right edge, growing longer the further ahead `x` is.
+fn ahead_marker(gizmos: &mut Gizmos, x: f32, view: (f32, f32), y: f32, color: impl Into<Color>) {
+    if x <= view.1 {
+        vertical_line(gizmos, x, color);
+        return;
+    }
+    let length = (20.0 + (x - view.1) / 20.0).min(200.0);
+    let tip = Vec2::new(view.1 - 10.0, y);
+    gizmos.arrow_2d(tip - Vec2::new(length, 0.0), tip, color);
+}
+
+pub fn draw_debug_overlay(
+    mut gizmos: Gizmos,
+    camera: Query<&Transform, With<CameraMarker>>,
+    car: Query<(&Car, &Transform)>,
+    obstacles: Query<(&Obstacle, &Transform)>,
+    level_assets: Res<LevelAssets>,
+    spawner: Res<ObstacleSpawner>,
+) {
+    let Ok(camera) = camera.get_single() else {
+        return;
+    };
+    let camera_x = camera.translation.x;
+    let view = (camera_x - WORLD_WIDTH / 2.0, camera_x + WORLD_WIDTH / 2.0);
+
+    for lane in 0..LANES {
+        let y = lane_y(&level_assets.y_values, lane);
+        gizmos.line_2d(Vec2::new(view.0, y), Vec2::new(view.1, y), css::DARK_GRAY);
+    }
+
+    // How far the car may drift from the camera.
+    vertical_line(&mut gizmos, camera_x - WORLD_WIDTH / 4.0, css::AQUA);
+    vertical_line(&mut gizmos, camera_x + WORLD_WIDTH / 4.0, css::AQUA);
+
+    // Patterns are laid out between one and two screens ahead of the camera,
+    // so the window and the next pattern start mostly show as edge arrows.
+    let top = WORLD_HEIGHT / 2.0;
+    ahead_marker(
+        &mut gizmos,
+        camera_x + WORLD_WIDTH,
+        view,
+        top - 40.0,
+        css::YELLOW,
+    );
+    ahead_marker(
+        &mut gizmos,
+        camera_x + 2.0 * WORLD_WIDTH,
+        view,
+        top - 70.0,
+        css::YELLOW,
+    );
+    ahead_marker(&mut gizmos, spawner.next_x, view, top - 100.0, css::ORANGE);
+
+    for n in 1..=level_assets.laps {
+        vertical_line(&mut gizmos, n as f32 * level_assets.lap_length, css::WHITE);
+    }
+
+    if let Ok((car, transform)) = car.get_single() {
+        gizmos.rect_2d(
+            transform.translation.truncate(),
+            0.0,
+            car.stats.size(),
+            css::LIME,
+        );
+    }
+    for (obstacle, transform) in obstacles.iter() {
+        gizmos.rect_2d(
+            transform.translation.truncate(),
+            0.0,
+            obstacle.size,
+            css::RED,
+        );
+        let footprint = obstacle.footprint(transform.translation);
+        gizmos.rect_2d(
+            Vec2::new(footprint.x, footprint.y),
+            0.0,
+            footprint.size,
+            css::FUCHSIA,
+        );
+    }
+}
@@ -0,0 +1,170 @@
+use std::f32::consts::PI;
+
+use bevy::ecs::system::EntityCommands;
+use bevy::prelude::*;
+
+/// Angle either side of straight up that the needle sweeps through.
+const SWEEP: f32 = PI * 0.75;
+const TICKS: usize = 13;
+const FACE_COLOR: Color = Color::srgba(0.0, 0.0, 0.0, 0.5);
+const TICK_COLOR: Color = Color::WHITE;
+const REDLINE_COLOR: Color = Color::srgb(0.9, 0.2, 0.2);
+const NEEDLE_COLOR: Color = Color::srgb(1.0, 0.55, 0.1);
+const HIGHLIGHT_COLOR: Color = Color::srgb(0.3, 0.8, 1.0);
+
+/// Round gauge with a needle, drawn with UI nodes. Whoever owns the dial sets
+/// these fields; the nodes only update when they change.
+#[derive(Component, Clone, Copy, Debug, PartialEq)]
+pub struct Dial {
+    pub value: f32,
+    pub max: f32,
+    /// Ticks at or past this value are drawn red.
+    pub redline: f32,
+    /// Lights the needle and rim, e.g. while boosting.
+    pub highlight: bool,
+}
+
+impl Dial {
+    fn fraction(&self, value: f32) -> f32 {
+        if self.max > 0.0 {
+            (value / self.max).clamp(0.0, 1.0)
+        } else {
+            0.0
+        }
+    }
+}
+
+#[derive(Component)]
+pub struct DialParts {
+    needle_pivot: Entity,
+    needle: Entity,
+    /// Tick bars and the fraction of the dial they mark.
+    ticks: Vec<(Entity, f32)>,
+}
+
+/// Rotation of a dial part pointing at `fraction` of the sweep. UI nodes are
+/// laid out y-down, so a positive angle turns clockwise on screen.
+fn dial_rotation(fraction: f32) -> Quat {
+    Quat::from_rotation_z(-SWEEP + 2.0 * SWEEP * fraction)
+}
+
+/// Full-size node turned around the dial's centre, so a bar placed at its top
+/// points outwards from the middle.
+fn pivot(fraction: f32) -> NodeBundle {
+    NodeBundle {
+        style: Style {
+            position_type: PositionType::Absolute,
+            width: Val::Percent(100.0),
+            height: Val::Percent(100.0),
+            ..default()
+        },
+        transform: Transform::from_rotation(dial_rotation(fraction)),
+        ..default()
+    }
+}
+
+fn bar(width: f32, top: f32, height: f32, color: Color) -> NodeBundle {
+    NodeBundle {
+        style: Style {
+            position_type: PositionType::Absolute,
+            left: Val::Percent(50.0 - width / 2.0),
+            top: Val::Percent(top),
+            width: Val::Percent(width),
+            height: Val::Percent(height),
+            ..default()
+        },
+        background_color: BackgroundColor(color),
+        ..default()
+    }
+}
+
+/// Spawns a dial as a child of `parent`. `style` places and sizes it; keep it
+/// square so the face stays round.
+pub fn spawn_dial<'a>(
+    parent: &'a mut ChildBuilder,
+    style: Style,
+    dial: Dial,
+) -> EntityCommands<'a> {
+    let mut ticks = Vec::new();
+    let mut needle = (Entity::PLACEHOLDER, Entity::PLACEHOLDER);
+
+    let mut face = parent.spawn(NodeBundle {
+        style: Style {
+            border: UiRect::all(Val::Px(3.0)),
+            ..style
+        },
+        border_color: BorderColor(TICK_COLOR),
+        border_radius: BorderRadius::MAX,
+        background_color: BackgroundColor(FACE_COLOR),
+        ..default()
+    });
+    face.with_children(|face| {
+        for n in 0..TICKS {
+            let fraction = n as f32 / (TICKS - 1) as f32;
+            face.spawn(pivot(fraction)).with_children(|pivot| {
+                ticks.push((pivot.spawn(bar(2.0, 4.0, 10.0, TICK_COLOR)).id(), fraction));
+            });
+        }
+        let mut needle_pivot = face.spawn(pivot(dial.fraction(dial.value)));
+        needle_pivot.with_children(|pivot| {
+            needle.1 = pivot.spawn(bar(3.0, 12.0, 38.0, NEEDLE_COLOR)).id();
+        });
+        needle.0 = needle_pivot.id();
+        // Hub over the needle's base.
+        face.spawn(NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                left: Val::Percent(44.0),
+                top: Val::Percent(44.0),
+                width: Val::Percent(12.0),
+                height: Val::Percent(12.0),
+                ..default()
+            },
+            border_radius: BorderRadius::MAX,
+            background_color: BackgroundColor(TICK_COLOR),
+            ..default()
+        });
+    });
+    face.insert((
+        dial,
+        DialParts {
+            needle_pivot: needle.0,
+            needle: needle.1,
+            ticks,
+        },
+    ));
+    face
+}
+
+pub fn update_dials(
+    mut dials: Query<(&Dial, &DialParts, &mut BorderColor), Changed<Dial>>,
+    mut transforms: Query<&mut Transform>,
+    mut colors: Query<&mut BackgroundColor>,
+) {
+    for (dial, parts, mut rim) in dials.iter_mut() {
+        // Layout owns the translation, so only the rotation is touched.
+        if let Ok(mut transform) = transforms.get_mut(parts.needle_pivot) {
+            transform.rotation = dial_rotation(dial.fraction(dial.value));
+        }
+        let (needle, ring) = if dial.highlight {
+            (HIGHLIGHT_COLOR, HIGHLIGHT_COLOR)
+        } else {
+            (NEEDLE_COLOR, TICK_COLOR)
+        };
+        if let Ok(mut color) = colors.get_mut(parts.needle) {
+            color.0 = needle;
+        }
+        rim.0 = ring;
+
+        let redline = dial.fraction(dial.redline);
+        for (tick, fraction) in parts.ticks.iter() {
+            if let Ok(mut color) = colors.get_mut(*tick) {
+                color.0 = if *fraction >= redline {
+                    REDLINE_COLOR
+                } else {
+                    TICK_COLOR
+                };
+            }
+        }
+    }
+}
@@ -0,0 +1,240 @@
+use bevy::prelude::*;
+
+use crate::{enter_pressed, is_unlocked, unlock_rule, GameState, SaveData, Unlockable};
+
+/// Handling numbers for a car. Sizes are in world units, speeds in world units
+/// per second.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct CarStats {
+    pub lateral_speed: f32,
+    pub max_speed: f32,
+    pub acceleration: f32,
+    pub width: f32,
+    pub height: f32,
+}
+
+impl CarStats {
+    pub fn size(&self) -> Vec2 {
+        Vec2 {
+            x: self.width,
+            y: self.height,
+        }
+    }
+}
+
+pub struct RosterCar {
+    pub name: &'static str,
+    pub texture: &'static str,
+    /// Folder in `assets` with the skin's wheel-spin and damage frames, which
+    /// each level builds its clips from.
+    pub skin: &'static str,
+    pub crash_frames: &'static [&'static str],
+    pub stats: CarStats,
+}
+
+pub const ROSTER: &[RosterCar] = &[
+    RosterCar {
+        name: "MOTORIST",
+        texture: "1084.png",
+        skin: "motorist",
+        crash_frames: &["1084.png"],
+        stats: CarStats {
+            lateral_speed: 500.0,
+            max_speed: 1100.0,
+            acceleration: 75.0,
+            width: 135.0,
+            height: 105.0,
+        },
+    },
+    RosterCar {
+        name: "URPLE",
+        texture: "1145.png",
+        skin: "urplecar",
+        crash_frames: &["1145.png", "urplecar/2.png", "urplecar/3.png"],
+        stats: CarStats {
+            lateral_speed: 560.0,
+            max_speed: 1200.0,
+            acceleration: 90.0,
+            width: 168.0,
+            height: 121.0,
+        },
+    },
+    RosterCar {
+        name: "TANK",
+        texture: "tank.png",
+        skin: "tank",
+        crash_frames: &["tank.png"],
+        stats: CarStats {
+            lateral_speed: 420.0,
+            max_speed: 950.0,
+            acceleration: 60.0,
+            width: 91.0,
+            height: 106.0,
+        },
+    },
+];
+
+pub const DEFAULT_STATS: CarStats = ROSTER[0].stats;
+
+#[derive(Resource, Default)]
+pub struct SelectedCar {
+    pub index: usize,
+}
+
+impl SelectedCar {
+    pub fn car(&self) -> &'static RosterCar {
+        &ROSTER[self.index]
+    }
+}
+
+#[derive(Component)]
+pub struct GarageUi;
+
+#[derive(Component)]
+pub struct GaragePreview;
+
+#[derive(Component)]
+pub struct GarageText;
+
+pub fn spawn_garage(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    garage: Query<&GarageUi>,
+) {
+    if !garage.is_empty() {
+        return;
+    }
+    let font = asset_server.load("GohuFont11NerdFont-Regular.ttf");
+
+    commands.spawn((
+        TextBundle::from_section(
+            "GARAGE",
+            TextStyle {
+                font_size: 100.0,
+                font: font.clone(),
+                ..default()
+            },
+        )
+        .with_text_justify(JustifyText::Center)
+        .with_style(Style {
+            position_type: PositionType::Absolute,
+            top: Val::Percent(10.0),
+            left: Val::Percent(25.0),
+            width: Val::Percent(50.0),
+            ..default()
+        }),
+        GarageUi,
+    ));
+    commands.spawn((
+        ImageBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                top: Val::Percent(30.0),
+                left: Val::Percent(42.0),
+                width: Val::Percent(16.0),
+                height: Val::Percent(20.0),
+                ..default()
+            },
+            ..default()
+        },
+        GaragePreview,
+        GarageUi,
+    ));
+    commands.spawn((
+        TextBundle::from_section(
+            "",
+            TextStyle {
+                font_size: 40.0,
+                font: font.clone(),
+                ..default()
+            },
+        )
+        .with_text_justify(JustifyText::Center)
+        .with_style(Style {
+            position_type: PositionType::Absolute,
+            top: Val::Percent(55.0),
+            left: Val::Percent(25.0),
+            width: Val::Percent(50.0),
+            ..default()
+        }),
+        GarageText,
+        GarageUi,
+    ));
+    commands.spawn((
+        TextBundle::from_section(
+            "< > CHOOSE   ENTER DRIVE",
+            TextStyle {
+                font_size: 30.0,
+                font,
+                ..default()
+            },
+        )
+        .with_text_justify(JustifyText::Center)
+        .with_style(Style {
+            position_type: PositionType::Absolute,
+            top: Val::Percent(85.0),
+            left: Val::Percent(25.0),
+            width: Val::Percent(50.0),
+            ..default()
+        }),
+        GarageUi,
+    ));
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn update_garage(
+    mut commands: Commands,
+    button_input: Res<ButtonInput<KeyCode>>,
+    asset_server: Res<AssetServer>,
+    save: Res<SaveData>,
+    mut selected: ResMut<SelectedCar>,
+    mut preview: Query<&mut UiImage, With<GaragePreview>>,
+    mut text: Query<&mut Text, With<GarageText>>,
+    garage: Query<Entity, With<GarageUi>>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    if preview.is_empty() || text.is_empty() {
+        return;
+    }
+
+    if button_input.just_pressed(KeyCode::ArrowRight) || button_input.just_pressed(KeyCode::KeyD) {
+        selected.index = (selected.index + 1) % ROSTER.len();
+    }
+    if button_input.just_pressed(KeyCode::ArrowLeft) || button_input.just_pressed(KeyCode::KeyA) {
+        selected.index = (selected.index + ROSTER.len() - 1) % ROSTER.len();
+    }
+
+    let car = selected.car();
+    let unlocked = is_unlocked(Unlockable::Car(selected.index), &save);
+
+    if enter_pressed(&button_input) && unlocked {
+        garage
+            .iter()
+            .for_each(|entity| commands.entity(entity).despawn());
+        next_state.set(GameState::LevelSelect);
+        return;
+    }
+
+    let mut image = preview.single_mut();
+    image.texture = asset_server.load(car.texture);
+    image.color = if unlocked {
+        Color::WHITE
+    } else {
+        Color::srgb(0.2, 0.2, 0.2)
+    };
+
+    let status = match unlock_rule(Unlockable::Car(selected.index)) {
+        Some(rule) if !unlocked => format!("\nLOCKED - {} TO UNLOCK", rule.condition.describe()),
+        _ => String::new(),
+    };
+    text.single_mut().sections[0].value = format!(
+        "{}\nSTEER {}  TOP {} MPH  ACCEL {}\nSIZE {}x{}{}",
+        car.name,
+        car.stats.lateral_speed,
+        car.stats.max_speed / 10.0,
+        car.stats.acceleration,
+        car.stats.width,
+        car.stats.height,
+        status,
+    );
+}
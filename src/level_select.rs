@@ -0,0 +1,200 @@
+use bevy::prelude::*;
+
+use crate::{
+    enter_pressed, is_unlocked, unlock_rule, GameState, Level, SaveData, Unlockable, LEVELS,
+};
+
+const SELECTED_BORDER: Color = Color::srgb(1.0, 0.85, 0.2);
+
+#[derive(Component)]
+pub struct LevelSelectUi;
+
+#[derive(Component)]
+pub struct LevelCard {
+    level: u8,
+}
+
+fn level_unlocked(level: u8, save: &SaveData) -> bool {
+    is_unlocked(Unlockable::Level(level), save)
+}
+
+fn card_text(level: u8, save: &SaveData) -> String {
+    let info = &LEVELS[level as usize - 1];
+    let record = save.level(level);
+    let status = if !level_unlocked(level, save) {
+        unlock_rule(Unlockable::Level(level))
+            .map(|rule| format!("LOCKED\n{}", rule.condition.describe()))
+            .unwrap_or_default()
+    } else if record.clean {
+        "CLEARED - NO CRASHES".to_string()
+    } else if record.cleared {
+        "CLEARED".to_string()
+    } else {
+        "NOT CLEARED".to_string()
+    };
+    format!(
+        "{} {}\nBEST {}\n{}",
+        level, info.name, record.best_score, status
+    )
+}
+
+pub fn spawn_level_select(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    save: Res<SaveData>,
+    mut level: ResMut<Level>,
+    level_select: Query<&LevelSelectUi>,
+) {
+    if !level_select.is_empty() {
+        return;
+    }
+    let font = asset_server.load("GohuFont11NerdFont-Regular.ttf");
+
+    // `Level` doubles as the cursor, so start on a level that can be driven.
+    if !(1..=LEVELS.len() as u8).contains(&level.level) || !level_unlocked(level.level, &save) {
+        level.level = 1;
+    }
+
+    commands.spawn((
+        TextBundle::from_section(
+            "SELECT LEVEL",
+            TextStyle {
+                font_size: 100.0,
+                font: font.clone(),
+                ..default()
+            },
+        )
+        .with_text_justify(JustifyText::Center)
+        .with_style(Style {
+            position_type: PositionType::Absolute,
+            top: Val::Percent(10.0),
+            left: Val::Percent(25.0),
+            width: Val::Percent(50.0),
+            ..default()
+        }),
+        LevelSelectUi,
+    ));
+
+    for (index, info) in LEVELS.iter().enumerate() {
+        let number = index as u8 + 1;
+        let unlocked = level_unlocked(number, &save);
+        commands
+            .spawn((
+                NodeBundle {
+                    style: Style {
+                        position_type: PositionType::Absolute,
+                        top: Val::Percent(28.0),
+                        left: Val::Percent(6.0 + 30.0 * index as f32),
+                        width: Val::Percent(28.0),
+                        height: Val::Percent(50.0),
+                        flex_direction: FlexDirection::Column,
+                        border: UiRect::all(Val::Px(4.0)),
+                        ..default()
+                    },
+                    border_color: BorderColor(Color::NONE),
+                    ..default()
+                },
+                LevelCard { level: number },
+                LevelSelectUi,
+            ))
+            .with_children(|parent| {
+                parent.spawn(ImageBundle {
+                    style: Style {
+                        width: Val::Percent(100.0),
+                        height: Val::Percent(55.0),
+                        ..default()
+                    },
+                    image: UiImage {
+                        texture: asset_server.load(info.background),
+                        color: if unlocked {
+                            Color::WHITE
+                        } else {
+                            Color::srgb(0.2, 0.2, 0.2)
+                        },
+                        ..default()
+                    },
+                    ..default()
+                });
+                parent.spawn(
+                    TextBundle::from_section(
+                        card_text(number, &save),
+                        TextStyle {
+                            font_size: 30.0,
+                            font: font.clone(),
+                            ..default()
+                        },
+                    )
+                    .with_text_justify(JustifyText::Center)
+                    .with_style(Style {
+                        margin: UiRect::top(Val::Percent(4.0)),
+                        ..default()
+                    }),
+                );
+            });
+    }
+
+    commands.spawn((
+        TextBundle::from_section(
+            "< > CHOOSE   ENTER RACE   ESC GARAGE",
+            TextStyle {
+                font_size: 30.0,
+                font,
+                ..default()
+            },
+        )
+        .with_text_justify(JustifyText::Center)
+        .with_style(Style {
+            position_type: PositionType::Absolute,
+            top: Val::Percent(85.0),
+            left: Val::Percent(20.0),
+            width: Val::Percent(60.0),
+            ..default()
+        }),
+        LevelSelectUi,
+    ));
+}
+
+pub fn update_level_select(
+    mut commands: Commands,
+    button_input: Res<ButtonInput<KeyCode>>,
+    save: Res<SaveData>,
+    mut level: ResMut<Level>,
+    mut cards: Query<(&LevelCard, &mut BorderColor)>,
+    level_select: Query<Entity, With<LevelSelectUi>>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    if cards.is_empty() {
+        return;
+    }
+
+    let count = LEVELS.len() as u8;
+    if button_input.just_pressed(KeyCode::ArrowRight) || button_input.just_pressed(KeyCode::KeyD) {
+        level.level = level.level % count + 1;
+    }
+    if button_input.just_pressed(KeyCode::ArrowLeft) || button_input.just_pressed(KeyCode::KeyA) {
+        level.level = (level.level + count - 2) % count + 1;
+    }
+
+    let leave = if enter_pressed(&button_input) && level_unlocked(level.level, &save) {
+        Some(GameState::LoadNextLevel)
+    } else if button_input.just_pressed(KeyCode::Escape) {
+        Some(GameState::Garage)
+    } else {
+        None
+    };
+    if let Some(state) = leave {
+        level_select
+            .iter()
+            .for_each(|entity| commands.entity(entity).despawn_recursive());
+        next_state.set(state);
+        return;
+    }
+
+    for (card, mut border) in cards.iter_mut() {
+        border.0 = if card.level == level.level {
+            SELECTED_BORDER
+        } else {
+            Color::NONE
+        };
+    }
+}
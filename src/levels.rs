@@ -1,7 +1,11 @@
 use std::time::Duration;
 
-use crate::CameraMarker;
 use crate::{car::*, menu::*};
+use crate::{
+    BackgroundLayer, CameraMarker, ClipKind, LevelAtlases, LevelRun, ObstacleKind, ObstacleSpawner,
+    PickupKind, RosterCar, SaveData, Score, SelectedCar, SpriteAnimations, SpriteClip,
+    WORLD_HEIGHT, WORLD_WIDTH,
+};
 use bevy::prelude::*;
 
 #[derive(Resource)]
@@ -9,20 +13,71 @@ pub struct Level {
     pub level: u8,
 }
 
+pub struct LevelInfo {
+    pub name: &'static str,
+    /// Picture of the level shown on the level select screen.
+    pub background: &'static str,
+}
+
+/// Levels in play order; `Level { level: n }` is `LEVELS[n - 1]`.
+pub const LEVELS: &[LevelInfo] = &[
+    LevelInfo {
+        name: "DOWNTOWN",
+        background: "1058.png",
+    },
+    LevelInfo {
+        name: "BACKROADS",
+        background: "backroads.png",
+    },
+    LevelInfo {
+        name: "RAINBOW ROAD",
+        background: "rainbowroad.png",
+    },
+];
+
 #[derive(Component)]
 pub struct LevelAssetMarker;
 
 #[derive(Resource, Default)]
 pub struct LevelAssets {
     pub car_texture: Handle<Image>,
+    /// Clips for the selected car, see `car_clips`.
+    pub car_animations: SpriteAnimations,
     pub obstacle_texture: Vec<Handle<Image>>,
+    pub obstacle_animations: Vec<SpriteAnimations>,
     pub obstacle_height: f32,
     pub obstacle_width: f32,
     pub obstacle_speed: f32,
+    pub obstacle_kinds: Vec<(ObstacleKind, u32)>,
+    pub roadblock_texture: Handle<Image>,
+    pub roadblock_height: f32,
+    pub roadblock_width: f32,
     pub y_values: [f32; 4],
-    pub background_texture: Handle<Image>,
+    /// Drawn back to front in order of `z`; the road itself is one of these.
+    pub background_layers: Vec<BackgroundLayer>,
     pub music: Handle<AudioSource>,
     pub lap_texture: Handle<Image>,
+    /// Distance of one lap in world units.
+    pub lap_length: f32,
+    /// Laps needed to finish the level.
+    pub laps: u32,
+    /// Icons in the lives row, each standing for an equal share of health.
+    pub lives: u32,
+    pub pattern_weights: Vec<(&'static str, u32)>,
+    pub pickup_kinds: Vec<(PickupKind, u32)>,
+}
+
+impl LevelAssets {
+    pub fn laps_completed(&self, x: f32) -> u32 {
+        (x / self.lap_length).max(0.0) as u32
+    }
+
+    /// Start of the lap being driven at `x`. Past the finish this stays on the
+    /// last lap so the lap reads as complete rather than starting over.
+    pub fn lap_start(&self, x: f32) -> f32 {
+        let lap = self.laps_completed(x).min(self.laps.saturating_sub(1));
+        lap as f32 * self.lap_length
+    }
 }
 
 pub fn game_over(
@@ -33,7 +88,7 @@ pub fn game_over(
     if car.is_empty() {
         return;
     }
-    if car.single().collision_counter == 5 {
+    if car.single().health <= 0.0 {
         commands.spawn((
             TextBundle::from_section(
                 "GAME OVER",
@@ -57,17 +112,20 @@ pub fn game_over(
     }
 }
 
+#[allow(clippy::too_many_arguments)]
 pub fn next_level(
     mut commands: Commands,
-    window: Query<&Window>,
     car: Query<&Transform, With<Car>>,
     mut next_state: ResMut<NextState<GameState>>,
+    level_assets: Res<LevelAssets>,
     mut level: ResMut<Level>,
+    mut save: ResMut<SaveData>,
+    score: Res<Score>,
+    run: Res<LevelRun>,
 ) {
-    let width = window.single().width();
-    let laps = (car.single().translation.x / (width) / 10.0) as u8;
+    let laps = level_assets.laps_completed(car.single().translation.x);
 
-    if laps == 1 {
+    if laps >= level_assets.laps {
         commands.spawn((
             TextBundle::from_section(
                 "SUCCESS",
@@ -87,6 +145,11 @@ pub fn next_level(
             }),
             MenuText,
         ));
+        save.record_clear(
+            level.level,
+            (score.score - run.start_score).floor() as u32,
+            run.crashes == 0,
+        );
         level.level += 1;
         next_state.set(GameState::Unloading);
     }
@@ -111,11 +174,45 @@ pub fn despawn_level(
     next_state.set(GameState::LoadNextLevel);
 }
 
+/// Frames a skin has for each wheel-spin and damage clip.
+const SPIN_FRAMES: usize = 3;
+/// Wheel spin of a wrecked car relative to an intact one.
+const WRECKED_SPIN: f32 = 0.6;
+const CRASH_FPS: f32 = 12.0;
+
+/// Clips for `car` on a level, with the wheels spinning at `spin_fps` frames
+/// a second.
+fn car_clips(car: &RosterCar, asset_server: &AssetServer, spin_fps: f32) -> SpriteAnimations {
+    let frames = |clip: &str| -> Vec<Handle<Image>> {
+        (0..SPIN_FRAMES)
+            .map(|frame| asset_server.load(format!("{}/{}{}.png", car.skin, clip, frame)))
+            .collect()
+    };
+    let crash = car
+        .crash_frames
+        .iter()
+        .map(|frame| asset_server.load(*frame))
+        .collect();
+    SpriteAnimations {
+        clips: vec![
+            (ClipKind::Idle, SpriteClip::new(frames("idle"), spin_fps)),
+            (ClipKind::Dented, SpriteClip::new(frames("dented"), spin_fps)),
+            (
+                ClipKind::Wrecked,
+                SpriteClip::new(frames("wrecked"), spin_fps * WRECKED_SPIN),
+            ),
+            (ClipKind::Crash, SpriteClip::new(crash, CRASH_FPS)),
+        ],
+    }
+}
+
 pub fn load_level(
     level: Res<Level>,
     mut assets: ResMut<LevelAssets>,
     asset_server: Res<AssetServer>,
     mut camera: Query<&mut Transform, With<CameraMarker>>,
+    mut spawner: ResMut<ObstacleSpawner>,
+    selected: Res<SelectedCar>,
     mut next_state: ResMut<NextState<GameState>>,
 ) {
     camera.single_mut().translation = Vec3 {
@@ -123,6 +220,8 @@ pub fn load_level(
         y: 0.0,
         z: 0.0,
     };
+    spawner.next_x = 0.0;
+    spawner.reseed(level.level);
 
     match level.level {
         1 => {
@@ -130,6 +229,15 @@ pub fn load_level(
                 obstacle_height: 105.0,
                 obstacle_width: 135.0,
                 obstacle_speed: 100.0,
+                obstacle_kinds: vec![
+                    (ObstacleKind::Cruiser, 6),
+                    (ObstacleKind::LaneChanger, 2),
+                    (ObstacleKind::BrakingTruck, 1),
+                    (ObstacleKind::Oscillator, 1),
+                ],
+                roadblock_texture: asset_server.load("1149.png"),
+                roadblock_height: 291.0,
+                roadblock_width: 202.0,
                 obstacle_texture: vec![
                     asset_server.load("1081.png"),
                     asset_server.load("1082.png"),
@@ -140,10 +248,54 @@ pub fn load_level(
                     (2.0 * 135.0 + 105.0 / 2.0 + 15.0),
                     (3.0 * 135.0 + 105.0 / 2.0 - 10.0),
                 ],
-                car_texture: asset_server.load("1084.png"),
-                background_texture: asset_server.load("1058.png"),
+                car_animations: car_clips(selected.car(), &asset_server, 10.0),
+                obstacle_animations: vec![
+                    SpriteAnimations {
+                        clips: vec![(
+                            ClipKind::Idle,
+                            SpriteClip::new(vec![asset_server.load("1081.png")], 0.0),
+                        )],
+                    },
+                    SpriteAnimations {
+                        clips: vec![(
+                            ClipKind::Idle,
+                            SpriteClip::new(vec![asset_server.load("1082.png")], 0.0),
+                        )],
+                    },
+                ],
+                background_layers: vec![
+                    BackgroundLayer {
+                        scroll_factor: 0.2,
+                        z: -1.0,
+                        ..BackgroundLayer::new(
+                            asset_server.load("scenery/neon_far.png"),
+                            Vec2::new(WORLD_WIDTH, WORLD_HEIGHT),
+                        )
+                    },
+                    BackgroundLayer::new(
+                        asset_server.load("scenery/neon_road.png"),
+                        Vec2::new(WORLD_WIDTH, WORLD_HEIGHT),
+                    ),
+                ],
                 music: asset_server.load("240bps.mp3"),
                 lap_texture: asset_server.load("1077.png"),
+                lap_length: 19200.0,
+                laps: 1,
+                lives: 5,
+                pattern_weights: vec![
+                    ("single", 2),
+                    ("gap", 3),
+                    ("zigzag", 3),
+                    ("convoy", 2),
+                    ("chicane", 2),
+                ],
+                pickup_kinds: vec![
+                    (PickupKind::Shield, 2),
+                    (PickupKind::SlowMotion, 1),
+                    (PickupKind::ScoreMultiplier, 2),
+                    (PickupKind::Repair, 1),
+                ],
+                ..default()
             };
         }
         2 => {
@@ -151,6 +303,10 @@ pub fn load_level(
                 obstacle_height: 291.0,
                 obstacle_width: 202.0,
                 obstacle_speed: 0.0,
+                obstacle_kinds: vec![(ObstacleKind::Roadblock, 1)],
+                roadblock_texture: asset_server.load("1149.png"),
+                roadblock_height: 291.0,
+                roadblock_width: 202.0,
                 obstacle_texture: vec![
                     asset_server.load("1149.png"),
                     asset_server.load("1149.png"),
@@ -161,10 +317,48 @@ pub fn load_level(
                     (2.0 * 135.0 + 105.0 / 2.0 + 15.0),
                     (3.0 * 135.0 + 105.0 / 2.0 - 10.0),
                 ],
-                car_texture: asset_server.load("1145.png"),
-                background_texture: asset_server.load("backroads.png"),
+                car_animations: car_clips(selected.car(), &asset_server, 12.0),
+                obstacle_animations: vec![
+                    SpriteAnimations {
+                        clips: vec![(
+                            ClipKind::Idle,
+                            SpriteClip::new(vec![asset_server.load("1149.png")], 0.0),
+                        )],
+                    },
+                    SpriteAnimations {
+                        clips: vec![(
+                            ClipKind::Idle,
+                            SpriteClip::new(vec![asset_server.load("1149.png")], 0.0),
+                        )],
+                    },
+                ],
+                background_layers: vec![
+                    BackgroundLayer {
+                        scroll_factor: 0.6,
+                        z: -1.0,
+                        ..BackgroundLayer::new(
+                            asset_server.load("scenery/backroads_far.png"),
+                            Vec2::new(WORLD_WIDTH, WORLD_HEIGHT),
+                        )
+                    },
+                    BackgroundLayer::new(
+                        asset_server.load("scenery/backroads_road.png"),
+                        Vec2::new(WORLD_WIDTH, WORLD_HEIGHT),
+                    ),
+                ],
                 music: asset_server.load("dui.mp3"),
                 lap_texture: asset_server.load("1077.png"),
+                lap_length: 16000.0,
+                laps: 2,
+                lives: 4,
+                pattern_weights: vec![("single", 4), ("zigzag", 3), ("chicane", 1)],
+                pickup_kinds: vec![
+                    (PickupKind::Shield, 3),
+                    (PickupKind::SlowMotion, 2),
+                    (PickupKind::ScoreMultiplier, 1),
+                    (PickupKind::Repair, 2),
+                ],
+                ..default()
             };
         }
         3 => {
@@ -172,6 +366,15 @@ pub fn load_level(
                 obstacle_height: 105.0,
                 obstacle_width: 135.0,
                 obstacle_speed: 0.0,
+                obstacle_kinds: vec![
+                    (ObstacleKind::Cruiser, 3),
+                    (ObstacleKind::LaneChanger, 1),
+                    (ObstacleKind::Roadblock, 1),
+                    (ObstacleKind::Oscillator, 2),
+                ],
+                roadblock_texture: asset_server.load("1149.png"),
+                roadblock_height: 291.0,
+                roadblock_width: 202.0,
                 obstacle_texture: vec![
                     asset_server.load("1081.png"),
                     asset_server.load("1082.png"),
@@ -182,14 +385,59 @@ pub fn load_level(
                     (2.0 * 135.0 + 105.0 / 2.0 + 15.0),
                     (3.0 * 135.0 + 105.0 / 2.0 - 10.0),
                 ],
-                car_texture: asset_server.load("tank.png"),
-                background_texture: asset_server.load("rainbowroad.png"),
+                car_animations: car_clips(selected.car(), &asset_server, 16.0),
+                obstacle_animations: vec![
+                    SpriteAnimations {
+                        clips: vec![(
+                            ClipKind::Idle,
+                            SpriteClip::new(vec![asset_server.load("1081.png")], 0.0),
+                        )],
+                    },
+                    SpriteAnimations {
+                        clips: vec![(
+                            ClipKind::Idle,
+                            SpriteClip::new(vec![asset_server.load("1082.png")], 0.0),
+                        )],
+                    },
+                ],
+                background_layers: vec![
+                    BackgroundLayer {
+                        scroll_factor: 0.3,
+                        z: -1.0,
+                        ..BackgroundLayer::new(
+                            asset_server.load("scenery/rainbow_far.png"),
+                            Vec2::new(WORLD_WIDTH, WORLD_HEIGHT),
+                        )
+                    },
+                    BackgroundLayer::new(
+                        asset_server.load("scenery/rainbow_road.png"),
+                        Vec2::new(1582.0, WORLD_HEIGHT),
+                    ),
+                ],
                 music: asset_server.load("dui.mp3"),
                 lap_texture: asset_server.load("1077.png"),
+                lap_length: 14400.0,
+                laps: 3,
+                lives: 3,
+                pattern_weights: vec![
+                    ("single", 1),
+                    ("gap", 2),
+                    ("zigzag", 2),
+                    ("convoy", 3),
+                    ("chicane", 3),
+                ],
+                pickup_kinds: vec![
+                    (PickupKind::Shield, 1),
+                    (PickupKind::SlowMotion, 2),
+                    (PickupKind::ScoreMultiplier, 2),
+                    (PickupKind::Repair, 1),
+                ],
+                ..default()
             };
         }
         _ => {}
     }
+    assets.car_texture = asset_server.load(selected.car().texture);
     next_state.set(GameState::Loading);
 }
 
@@ -222,9 +470,13 @@ pub fn despawn_loading_screen(
     mut commands: Commands,
     asset_server: Res<AssetServer>,
     level_assets: Res<LevelAssets>,
+    atlases: Res<LevelAtlases>,
     mut next_state: ResMut<NextState<GameState>>,
     menutext: Query<Entity, With<MenuText>>,
 ) {
+    if !atlases.ready {
+        return;
+    }
     if asset_server
         .get_load_state(&level_assets.car_texture)
         .unwrap()
@@ -247,12 +499,17 @@ pub fn despawn_loading_screen(
         return;
     }
     if asset_server
-        .get_load_state(&level_assets.background_texture)
+        .get_load_state(&level_assets.roadblock_texture)
         .unwrap()
         != bevy::asset::LoadState::Loaded
     {
         return;
     }
+    if level_assets.background_layers.iter().any(|layer| {
+        asset_server.get_load_state(&layer.texture).unwrap() != bevy::asset::LoadState::Loaded
+    }) {
+        return;
+    }
 
     if !menutext.is_empty() {
         commands.entity(menutext.single()).despawn();
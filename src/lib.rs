@@ -0,0 +1,108 @@
+use bevy::prelude::*;
+
+mod ui;
+pub use ui::*;
+
+mod menu;
+pub use menu::*;
+
+mod car;
+pub use car::*;
+
+mod obstacle;
+pub use obstacle::*;
+
+mod levels;
+pub use levels::*;
+
+mod pattern;
+pub use pattern::*;
+
+mod pickup;
+pub use pickup::*;
+
+mod boost;
+pub use boost::*;
+
+mod damage;
+pub use damage::*;
+
+mod animation;
+pub use animation::*;
+
+mod bitmap_font;
+pub use bitmap_font::*;
+
+mod minimap;
+pub use minimap::*;
+
+mod dial;
+pub use dial::*;
+
+#[cfg(feature = "dev-tools")]
+mod debug;
+#[cfg(feature = "dev-tools")]
+pub use debug::*;
+
+mod save;
+pub use save::*;
+
+mod garage;
+pub use garage::*;
+
+mod progression;
+pub use progression::*;
+
+mod level_select;
+pub use level_select::*;
+
+mod cli;
+pub use cli::*;
+
+mod replay;
+pub use replay::*;
+
+mod autopilot;
+pub use autopilot::*;
+
+mod attract;
+pub use attract::*;
+
+mod simulation;
+pub use simulation::*;
+
+/// The race itself: driving, traffic, pickups and how a run ends. The game
+/// and the headless simulation both add it, so they play by the same rules.
+pub struct GameplayPlugin;
+
+impl Plugin for GameplayPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ObstacleSpawner>()
+            .init_resource::<PickupSpawner>()
+            .init_resource::<PowerUps>()
+            .init_resource::<Boost>()
+            .init_resource::<CrashRecovery>()
+            .init_resource::<LevelRun>()
+            .init_resource::<AttractMode>()
+            .add_systems(
+                Update,
+                (
+                    update_boost,
+                    update_car,
+                    camera_tracking,
+                    update_obstacles,
+                    spawn_new_obstacles,
+                    spawn_pickups,
+                    detect_collision,
+                    collect_pickups,
+                    update_power_ups,
+                    update_damage_state,
+                    update_invulnerability,
+                    game_over,
+                    next_level.run_if(player_driving),
+                )
+                    .chain()
+                    .run_if(in_state(GameState::Running)),
+            );
+    }
+}
@@ -1,71 +1,97 @@
-use bevy::prelude::*;
-use bevy_inspector_egui::quick::WorldInspectorPlugin;
-use bevy_screen_diagnostics::{ScreenDiagnosticsPlugin, ScreenFrameDiagnosticsPlugin};
+use bevy::{audio::AudioPlugin, prelude::*};
 
-mod ui;
-use ui::*;
-
-mod menu;
-use menu::*;
-
-mod car;
-use car::*;
-
-mod obstacle;
-use obstacle::*;
-
-mod levels;
-use levels::*;
+use midnight_motorist::*;
 
 fn main() {
-    App::new()
+    let args = CliArgs::from_env();
+    let replay = args.load_replay();
+    let seed = args.seed(replay.as_ref());
+    let level = replay.as_ref().map(|replay| replay.recording.level).or(args.level);
+    let car = replay.as_ref().map_or(0, |replay| replay.recording.car);
+
+    let mut app = App::new();
+    app
         .add_plugins(DefaultPlugins.set(WindowPlugin {
-            primary_window: Some(Window {
-                title: "Midnight Motorist".into(),
-                name: Some("Midnight Motorist".into()),
-                resolution: (1280.0, 720.0).into(),
-                enabled_buttons: bevy::window::EnabledButtons {
-                    maximize: false,
-                    ..Default::default()
-                },
-                resizable: false,
-                ..default()
-            }),
+            primary_window: Some(args.window()),
+            ..default()
+        }).set(AudioPlugin {
+            global_volume: args.volume(),
             ..default()
         }))
-        .add_plugins(ScreenDiagnosticsPlugin::default())
-        .add_plugins(ScreenFrameDiagnosticsPlugin)
-        .add_plugins(WorldInspectorPlugin::new())
         .add_systems(Startup, spawn_ui)
-        .add_systems(Startup, spawn_score)
+        .insert_resource(Score::default())
+        .insert_resource(BitmapFont::default())
+        .add_systems(Startup, load_bitmap_font)
+        .add_systems(Update, (build_bitmap_font, render_bitmap_text).chain())
         .add_systems(Startup, spawn_camera)
         .add_systems(Update, update_menu)
+        .add_systems(Update, (scale_ui_to_window, toggle_fullscreen, show_hud))
 
-        .insert_resource(Level { level: 1 })
+        .insert_resource(Level { level: level.unwrap_or(1) })
         .insert_resource(LevelAssets { ..default() })
         .insert_resource(CountdownAssets { ..default() })
+        .insert_resource(ObstacleSpawner::seeded(seed))
+        .add_plugins(GameplayPlugin)
+        .add_systems(Startup, spawn_power_up_hud)
+        .insert_resource(LevelAtlases::default())
+        .add_systems(Startup, spawn_boost_gauge)
+        .add_systems(Startup, spawn_health_bar)
+        .add_systems(Startup, spawn_lives)
+        .add_systems(Startup, spawn_minimap)
         .add_systems(Startup, spawn_countdown_assets)
 
-        .insert_state(GameState::LoadNextLevel)
+        .insert_resource(SaveData::load())
+        .insert_resource(SelectedCar { index: car })
+        .insert_state(args.start_state())
+        .add_systems(Update, (spawn_garage, update_garage).chain().run_if(in_state(GameState::Garage)))
+        .add_systems(Update, start_attract_mode.after(update_garage).run_if(in_state(GameState::Garage)))
+        .add_systems(Update, drive_attract_mode.before(update_menu).before(update_boost).before(update_car))
+        .add_systems(Update, (spawn_level_select, update_level_select).chain().run_if(in_state(GameState::LevelSelect)))
+        .add_systems(Update, update_high_score.run_if(in_state(GameState::Running).and_then(player_driving)))
+        .add_systems(OnExit(GameState::Running), store_save.run_if(player_driving))
+        .add_systems(Update, reset_level_run.run_if(in_state(GameState::LoadNextLevel)))
+        .add_systems(Update, (check_unlocks, update_unlock_notices))
         .add_systems(Update, despawn_level.run_if(in_state(GameState::Unloading)))
         .add_systems(Update, spawn_loading_screen.run_if(in_state(GameState::LoadNextLevel)))
         .add_systems(Update, load_level.run_if(in_state(GameState::LoadNextLevel)))
+        .add_systems(Update, reset_power_ups.after(load_level).run_if(in_state(GameState::LoadNextLevel)))
+        .add_systems(Update, reset_boost.run_if(in_state(GameState::LoadNextLevel)))
+        .add_systems(Update, reset_level_atlases.run_if(in_state(GameState::LoadNextLevel)))
+        .add_systems(Update, build_level_atlases.run_if(in_state(GameState::Loading)))
         .add_systems(Update, (spawn_car, spawn_background).after(load_level).run_if(in_state(GameState::Loading)))
         .add_systems(Update, despawn_loading_screen.run_if(in_state(GameState::Loading)))
 
         .add_systems(Update, start_countdown.run_if(in_state(GameState::Countdown)))
+        .add_systems(Update, (select_car_clip, animate_sprites).chain().run_if(in_state(GameState::Running).or_else(in_state(GameState::Countdown))))
 
         .add_systems(Update, update_score.run_if(in_state(GameState::Running)))
         .add_systems(Update, start_music.run_if(in_state(GameState::Running)))
-        .add_systems(Update, camera_tracking.run_if(in_state(GameState::Running)))
-        .add_systems(Update, update_laps.run_if(in_state(GameState::Running)))
+        .add_systems(Update, (update_laps, update_laps_text).chain().run_if(in_state(GameState::Running)))
         .add_systems(Update, update_speed.run_if(in_state(GameState::Running)))
+        .insert_resource(HudOptions::default())
+        .add_systems(Update, (update_speed_dial.run_if(in_state(GameState::Running)), toggle_speed_dial, update_dials).chain())
+        .add_systems(Update, (sync_minimap_icons, update_minimap).chain())
+        .add_systems(Update, update_lives.after(detect_collision).run_if(in_state(GameState::Running).or_else(in_state(GameState::Countdown))))
         .add_systems(Update, update_background.run_if(in_state(GameState::Running)))
-        .add_systems(Update, update_car.run_if(in_state(GameState::Running)))
-        .add_systems(Update, update_obstacles.run_if(in_state(GameState::Running)))
-        .add_systems(Update, spawn_new_obstacles.after(update_obstacles).run_if(in_state(GameState::Running)))
-        .add_systems(Update, game_over.run_if(in_state(GameState::Running)))
-        .add_systems(Update, next_level.run_if(in_state(GameState::Running)))
-        .add_systems(Update, detect_collision.run_if(in_state(GameState::Running)))
-        .run();
+        .add_systems(Update, update_power_up_hud.after(update_power_ups).run_if(in_state(GameState::Running)))
+        .add_systems(Update, update_health_bar.after(update_invulnerability).run_if(in_state(GameState::Running)))
+        .add_systems(Update, update_boost_gauge.after(update_boost).run_if(in_state(GameState::Running)));
+
+    app.add_systems(Update, reset_recorder.run_if(in_state(GameState::LoadNextLevel).and_then(player_driving)))
+        .add_systems(Update, spawn_ghost.after(load_level).run_if(in_state(GameState::Loading)))
+        .add_systems(Update, record_car.run_if(in_state(GameState::Running).and_then(player_driving)))
+        .add_systems(Update, update_ghost.run_if(in_state(GameState::Running)))
+        .add_systems(OnExit(GameState::Running), store_recording.run_if(player_driving));
+    if let Some(replay) = replay {
+        app.insert_resource(replay);
+    }
+    if let Some(recorder) = args.recorder(seed) {
+        app.insert_resource(recorder);
+    }
+
+    #[cfg(feature = "dev-tools")]
+    app.add_plugins(DevToolsPlugin)
+        .insert_resource(DebugOverlay { enabled: args.debug });
+
+    app.run();
 }
@@ -2,6 +2,8 @@ use bevy::prelude::*;
 
 #[derive(States, Debug, Clone, PartialEq, Eq, Hash)]
 pub enum GameState {
+    Garage,
+    LevelSelect,
     LoadNextLevel,
     Running,
     Paused,
@@ -13,6 +15,12 @@ pub enum GameState {
 #[derive(Component)]
 pub struct MenuText;
 
+/// Enter on its own, so Alt+Enter toggles fullscreen without confirming menus.
+pub fn enter_pressed(button_input: &ButtonInput<KeyCode>) -> bool {
+    button_input.just_pressed(KeyCode::Enter)
+        && !button_input.any_pressed([KeyCode::AltLeft, KeyCode::AltRight])
+}
+
 pub fn spawn_menu(mut commands: Commands, asset_server: ResMut<AssetServer>) {
     commands.spawn((
         TextBundle::from_section(
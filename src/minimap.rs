@@ -0,0 +1,218 @@
+use bevy::prelude::*;
+
+use crate::{Car, Hud, LevelAssets, Obstacle};
+
+/// Slices the lap is split into when showing how crowded the road ahead is.
+const DENSITY_BINS: usize = 48;
+/// Obstacles in one slice that colour it fully.
+const DENSITY_FULL: f32 = 3.0;
+const FINISH_COLOR: Color = Color::srgb(1.0, 0.85, 0.2);
+const LAP_MARKER_COLOR: Color = Color::srgb(0.6, 0.6, 0.6);
+
+/// Shows an entity on the lap minimap. The player car carries one; other
+/// cars such as a replay ghost can carry their own.
+#[derive(Component, Clone, Copy)]
+pub struct MinimapMarker {
+    pub color: Color,
+}
+
+#[derive(Component)]
+pub struct Minimap;
+
+#[derive(Component)]
+pub struct MinimapIcon {
+    target: Entity,
+}
+
+#[derive(Component)]
+pub struct MinimapDensity {
+    bin: usize,
+}
+
+#[derive(Component)]
+pub struct MinimapFinish;
+
+/// Obstacles counted per slice of the lap starting at `lap_start`, ignoring
+/// anything behind `from` or outside the lap.
+pub fn obstacle_density(
+    positions: impl IntoIterator<Item = f32>,
+    lap_start: f32,
+    lap_length: f32,
+    from: f32,
+) -> [u32; DENSITY_BINS] {
+    let mut bins = [0; DENSITY_BINS];
+    for x in positions {
+        let progress = (x - lap_start) / lap_length;
+        if x < from || !(0.0..1.0).contains(&progress) {
+            continue;
+        }
+        bins[(progress * DENSITY_BINS as f32) as usize] += 1;
+    }
+    bins
+}
+
+pub fn spawn_minimap(mut commands: Commands) {
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    top: Val::Percent(3.0),
+                    left: Val::Percent(30.0),
+                    width: Val::Percent(40.0),
+                    height: Val::Percent(2.0),
+                    border: UiRect::all(Val::Px(2.0)),
+                    ..default()
+                },
+                border_color: BorderColor(Color::WHITE),
+                background_color: BackgroundColor(Color::srgba(0.0, 0.0, 0.0, 0.5)),
+                ..default()
+            },
+            Minimap,
+            Hud,
+        ))
+        .with_children(|parent| {
+            for bin in 0..DENSITY_BINS {
+                parent.spawn((
+                    NodeBundle {
+                        style: Style {
+                            width: Val::Percent(100.0 / DENSITY_BINS as f32),
+                            height: Val::Percent(100.0),
+                            ..default()
+                        },
+                        background_color: BackgroundColor(Color::NONE),
+                        ..default()
+                    },
+                    MinimapDensity { bin },
+                ));
+            }
+            parent.spawn((
+                NodeBundle {
+                    style: Style {
+                        position_type: PositionType::Absolute,
+                        right: Val::Px(0.0),
+                        width: Val::Px(4.0),
+                        height: Val::Percent(100.0),
+                        ..default()
+                    },
+                    background_color: BackgroundColor(LAP_MARKER_COLOR),
+                    ..default()
+                },
+                MinimapFinish,
+            ));
+        });
+}
+
+/// Keeps one icon on the minimap for every `MinimapMarker` in the world.
+pub fn sync_minimap_icons(
+    mut commands: Commands,
+    minimap: Query<Entity, With<Minimap>>,
+    added: Query<(Entity, &MinimapMarker), Added<MinimapMarker>>,
+    icons: Query<(Entity, &MinimapIcon)>,
+    markers: Query<(), With<MinimapMarker>>,
+) {
+    for (entity, icon) in icons.iter() {
+        if !markers.contains(icon.target) {
+            commands.entity(entity).despawn_recursive();
+        }
+    }
+
+    let Ok(minimap) = minimap.get_single() else {
+        return;
+    };
+    for (target, marker) in added.iter() {
+        let icon = commands
+            .spawn((
+                NodeBundle {
+                    style: Style {
+                        position_type: PositionType::Absolute,
+                        top: Val::Percent(-50.0),
+                        width: Val::Px(8.0),
+                        height: Val::Percent(200.0),
+                        ..default()
+                    },
+                    background_color: BackgroundColor(marker.color),
+                    visibility: Visibility::Hidden,
+                    z_index: ZIndex::Local(1),
+                    ..default()
+                },
+                MinimapIcon { target },
+            ))
+            .id();
+        commands.entity(minimap).add_child(icon);
+    }
+}
+
+pub fn update_minimap(
+    level_assets: Res<LevelAssets>,
+    car: Query<&Transform, With<Car>>,
+    obstacles: Query<&Transform, With<Obstacle>>,
+    targets: Query<&Transform, With<MinimapMarker>>,
+    mut icons: Query<(&MinimapIcon, &mut Style, &mut Visibility)>,
+    mut density: Query<(&MinimapDensity, &mut BackgroundColor), Without<MinimapFinish>>,
+    mut finish: Query<&mut BackgroundColor, With<MinimapFinish>>,
+) {
+    let Ok(car) = car.get_single() else {
+        return;
+    };
+    let car_x = car.translation.x;
+    let lap_start = level_assets.lap_start(car_x);
+    let lap_length = level_assets.lap_length;
+
+    for (icon, mut style, mut visibility) in icons.iter_mut() {
+        let Ok(target) = targets.get(icon.target) else {
+            continue;
+        };
+        let progress = (target.translation.x - lap_start) / lap_length;
+        if (0.0..=1.0).contains(&progress) {
+            style.left = Val::Percent(progress * 100.0);
+            *visibility = Visibility::Inherited;
+        } else {
+            *visibility = Visibility::Hidden;
+        }
+    }
+
+    let bins = obstacle_density(
+        obstacles.iter().map(|transform| transform.translation.x),
+        lap_start,
+        lap_length,
+        car_x,
+    );
+    for (cell, mut color) in density.iter_mut() {
+        let amount = (bins[cell.bin] as f32 / DENSITY_FULL).min(1.0);
+        color.0 = Color::srgba(0.9, 0.2, 0.2, 0.8 * amount);
+    }
+
+    // The last lap marker is the finish line.
+    let final_lap = level_assets.laps_completed(car_x) + 1 >= level_assets.laps;
+    finish.single_mut().0 = if final_lap {
+        FINISH_COLOR
+    } else {
+        LAP_MARKER_COLOR
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn density_only_counts_the_road_ahead_in_this_lap() {
+        let lap_length = DENSITY_BINS as f32 * 100.0;
+        let lap_start = lap_length;
+        let positions = [
+            // Behind the car.
+            lap_start + 200.0,
+            // Ahead, sharing a bin.
+            lap_start + 1450.0,
+            lap_start + 1499.0,
+            // Last bin of the lap, then into the next lap.
+            lap_start + lap_length - 50.0,
+            lap_start + lap_length + 10.0,
+        ];
+        let bins = obstacle_density(positions, lap_start, lap_length, lap_start + 1200.0);
+        assert_eq!(bins[14], 2);
+        assert_eq!(bins[DENSITY_BINS - 1], 1);
+        assert_eq!(bins.iter().sum::<u32>(), 3);
+    }
+}
@@ -1,107 +1,643 @@
 use bevy::prelude::*;
+use rand::distributions::WeightedIndex;
 use rand::prelude::*;
+use rand::rngs::StdRng;
 
-use crate::{CameraMarker, Car, CarState, LevelAssets, LevelAssetMarker}; 
+use crate::{
+    crash_damage, find_pattern, is_passable, lane_y, Boost, CameraMarker, Car, CarState,
+    CrashRecovery, Footprint, LevelAssetMarker, LevelAssets, LevelRun, PowerUps, SelectedCar,
+    SheetId, SpriteAnimator, LANES, WORLD_WIDTH,
+};
 
-const CARHEIGHT: f32 = 105.0;
-const CARWIDTH: f32 = 135.0;
+// Behaviour tuning.
+const SIGNAL_SECONDS: f32 = 0.8;
+const SIGNAL_BLINK: f32 = 0.15;
+const LANE_CHANGE_SPEED: f32 = 150.0;
+const BRAKE_DECELERATION: f32 = 120.0;
+/// Slowest a braking truck cruises before it brakes, so it still has speed to
+/// shed on levels where the rest of the traffic stands still.
+const TRUCK_MIN_SPEED: f32 = 100.0;
+const OSCILLATE_AMPLITUDE: f32 = 60.0;
+const OSCILLATE_FREQUENCY: f32 = 2.0;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ObstacleKind {
+    Cruiser,
+    LaneChanger,
+    BrakingTruck,
+    Roadblock,
+    Oscillator,
+}
+
+pub enum ObstacleBehaviour {
+    Cruise,
+    LaneChange {
+        signal: Timer,
+        target_y: f32,
+    },
+    Brake {
+        braking: bool,
+    },
+    Stationary,
+    Oscillate {
+        base_y: f32,
+        amplitude: f32,
+        phase: f32,
+    },
+}
 
 #[derive(Component)]
 pub struct Obstacle {
     speed: f32,
+    pub size: Vec2,
+    pub kind: ObstacleKind,
+    behaviour: ObstacleBehaviour,
+}
+
+impl Obstacle {
+    /// Slowest and fastest the obstacle may go from here on along the road,
+    /// negative for oncoming traffic. Only braking trucks change speed.
+    pub fn speed_range(&self) -> (f32, f32) {
+        match self.behaviour {
+            ObstacleBehaviour::Brake { .. } => (self.speed.min(0.0), self.speed.max(0.0)),
+            _ => (self.speed, self.speed),
+        }
+    }
+
+    /// Space the obstacle may occupy over its lifetime, not just where it is now.
+    pub fn footprint(&self, translation: Vec3) -> Footprint {
+        let (y, height) = match self.behaviour {
+            ObstacleBehaviour::LaneChange { target_y, .. } => (
+                (translation.y + target_y) / 2.0,
+                self.size.y + (translation.y - target_y).abs(),
+            ),
+            ObstacleBehaviour::Oscillate {
+                base_y, amplitude, ..
+            } => (base_y, self.size.y + 2.0 * amplitude),
+            _ => (translation.y, self.size.y),
+        };
+        Footprint {
+            x: translation.x,
+            y,
+            size: Vec2 {
+                x: self.size.x,
+                y: height,
+            },
+        }
+    }
 }
 
 pub fn update_obstacles(
     mut commands: Commands,
-    mut obstacles: Query<(Entity, &Obstacle, &mut Transform), Without<CameraMarker>>,
+    mut obstacles: Query<
+        (Entity, &mut Obstacle, &mut Transform, &mut Sprite),
+        Without<CameraMarker>,
+    >,
     camera: Query<&Transform, (With<CameraMarker>, Without<Obstacle>)>,
     time: Res<Time>,
-    window: Query<&Window>,
 ) {
-    let width = window.single().width();
-    for (obstacle_entity, obstacle, mut obstacle_transform) in obstacles.iter_mut() {
-        if obstacle_transform.translation.x < camera.single().translation.x - width / 2.0 {
+    let camera_x = camera.single().translation.x;
+    let delta = time.delta_seconds();
+
+    for (obstacle_entity, mut obstacle, mut obstacle_transform, mut sprite) in obstacles.iter_mut()
+    {
+        if obstacle_transform.translation.x < camera_x - WORLD_WIDTH / 2.0 {
             commands.entity(obstacle_entity).despawn();
-        } else {
-            obstacle_transform.translation.x += obstacle.speed * time.delta_seconds();
+            continue;
+        }
+
+        let mut speed = obstacle.speed;
+        match &mut obstacle.behaviour {
+            ObstacleBehaviour::Cruise | ObstacleBehaviour::Stationary => {}
+            ObstacleBehaviour::LaneChange { signal, target_y } => {
+                if !signal.finished() {
+                    signal.tick(time.delta());
+                    let blink = ((signal.elapsed_secs() / SIGNAL_BLINK) as u32) % 2 == 0;
+                    sprite.color = if blink {
+                        Color::srgb(1.0, 0.6, 0.0)
+                    } else {
+                        Color::WHITE
+                    };
+                } else {
+                    sprite.color = Color::WHITE;
+                    let step = LANE_CHANGE_SPEED * delta;
+                    let offset = *target_y - obstacle_transform.translation.y;
+                    obstacle_transform.translation.y += offset.clamp(-step, step);
+                }
+            }
+            ObstacleBehaviour::Brake { braking } => {
+                if !*braking && obstacle_transform.translation.x < camera_x + WORLD_WIDTH * 0.75 {
+                    *braking = true;
+                    sprite.color = Color::srgb(1.0, 0.4, 0.4);
+                }
+                if *braking {
+                    let step = BRAKE_DECELERATION * delta;
+                    speed -= speed.clamp(-step, step);
+                }
+            }
+            ObstacleBehaviour::Oscillate {
+                base_y,
+                amplitude,
+                phase,
+            } => {
+                *phase += OSCILLATE_FREQUENCY * delta;
+                obstacle_transform.translation.y = *base_y + *amplitude * phase.sin();
+            }
+        }
+        obstacle.speed = speed;
+        obstacle_transform.translation.x += obstacle.speed * delta;
+    }
+}
+
+/// Draws for pickups come from their own stream; pattern `n` uses `n + 1`.
+pub const PICKUP_STREAM: u64 = 0;
+
+#[derive(Resource, Default)]
+pub struct ObstacleSpawner {
+    pub next_x: f32,
+    /// Fixed seed from the command line; each level is then laid out the same
+    /// way every time it's played.
+    pub seed: Option<u64>,
+    level: u8,
+    /// Patterns laid out so far on this level, including empty stretches.
+    patterns: u64,
+    /// Meeting footprints of everything laid out that the car hasn't reached.
+    planned: Vec<Footprint>,
+}
+
+impl ObstacleSpawner {
+    pub fn seeded(seed: Option<u64>) -> Self {
+        ObstacleSpawner { seed, ..default() }
+    }
+
+    /// Starts laying out `level` from the beginning.
+    pub fn reseed(&mut self, level: u8) {
+        self.level = level;
+        self.patterns = 0;
+        self.planned.clear();
+    }
+
+    /// Draws for one `stream` of the current level. With a fixed seed a
+    /// stream comes out the same however the level is driven; otherwise it's
+    /// random.
+    pub fn rng(&self, stream: u64) -> StdRng {
+        match self.seed {
+            Some(seed) => StdRng::seed_from_u64(
+                seed ^ (self.level as u64) << 32 ^ stream.wrapping_mul(0x9E37_79B9_7F4A_7C15),
+            ),
+            None => StdRng::from_entropy(),
         }
     }
 }
 
+const PATTERN_ATTEMPTS: usize = 5;
+
+fn build_obstacle(
+    kind: ObstacleKind,
+    x: f32,
+    lane: usize,
+    lanes: &[f32],
+    level_assets: &LevelAssets,
+) -> (Obstacle, Vec3, Handle<Image>, Option<SheetId>) {
+    let oncoming = lane >= LANES / 2;
+    let speed = if oncoming {
+        -level_assets.obstacle_speed
+    } else {
+        level_assets.obstacle_speed
+    };
+    let size = Vec2 {
+        x: level_assets.obstacle_width,
+        y: level_assets.obstacle_height,
+    };
+    let texture = level_assets.obstacle_texture[oncoming as usize].clone();
+    // Neighbouring lane on the same side of the road.
+    let neighbour = if lane % 4 == 3 { lane - 1 } else { lane + 1 };
+
+    let (obstacle, y, texture) = match kind {
+        ObstacleKind::Cruiser => (
+            Obstacle {
+                speed,
+                size,
+                kind,
+                behaviour: ObstacleBehaviour::Cruise,
+            },
+            lanes[lane],
+            texture,
+        ),
+        ObstacleKind::LaneChanger => (
+            Obstacle {
+                speed,
+                size,
+                kind,
+                behaviour: ObstacleBehaviour::LaneChange {
+                    signal: Timer::from_seconds(SIGNAL_SECONDS, TimerMode::Once),
+                    target_y: lanes[neighbour],
+                },
+            },
+            lanes[lane],
+            texture,
+        ),
+        ObstacleKind::BrakingTruck => (
+            Obstacle {
+                speed: if oncoming {
+                    -level_assets.obstacle_speed.max(TRUCK_MIN_SPEED)
+                } else {
+                    level_assets.obstacle_speed.max(TRUCK_MIN_SPEED)
+                },
+                size,
+                kind,
+                behaviour: ObstacleBehaviour::Brake { braking: false },
+            },
+            lanes[lane],
+            texture,
+        ),
+        ObstacleKind::Roadblock => (
+            Obstacle {
+                speed: 0.0,
+                size: Vec2 {
+                    x: level_assets.roadblock_width,
+                    y: level_assets.roadblock_height,
+                },
+                kind,
+                behaviour: ObstacleBehaviour::Stationary,
+            },
+            (lanes[lane] + lanes[neighbour]) / 2.0,
+            level_assets.roadblock_texture.clone(),
+        ),
+        ObstacleKind::Oscillator => (
+            Obstacle {
+                speed,
+                size,
+                kind,
+                behaviour: ObstacleBehaviour::Oscillate {
+                    base_y: lanes[lane],
+                    amplitude: OSCILLATE_AMPLITUDE,
+                    phase: 0.0,
+                },
+            },
+            lanes[lane],
+            texture,
+        ),
+    };
+    let translation = Vec3 {
+        x: x + obstacle.size.x / 2.0,
+        y,
+        z: 1.0,
+    };
+    let sheet = match kind {
+        ObstacleKind::Roadblock => None,
+        _ => Some(SheetId::Obstacle(oncoming as usize)),
+    };
+    (obstacle, translation, texture, sheet)
+}
+
 pub fn spawn_new_obstacles(
     mut commands: Commands,
-    obstacles: Query<&Transform, (With<Obstacle>, Without<CameraMarker>)>,
-    camera: Query<&Transform, (With<CameraMarker>, Without<Obstacle>)>,
-    window: Query<&Window>,
-    level_assets: ResMut<LevelAssets>,
+    camera: Query<&Transform, With<CameraMarker>>,
+    level_assets: Res<LevelAssets>,
+    mut spawner: ResMut<ObstacleSpawner>,
+    selected: Res<SelectedCar>,
 ) {
-    let width = window.single().width();
-    let window_scale = 1080.0 / window.single().height();
+    let camera_x = camera.single().translation.x;
 
-    if obstacles.iter().count() > 10 {
+    spawner.next_x = spawner.next_x.max(camera_x + WORLD_WIDTH);
+    if spawner.next_x > camera_x + 2.0 * WORLD_WIDTH {
         return;
     }
 
-    let offset = camera.single().translation.x + width;
+    let Ok(pattern_weights) =
+        WeightedIndex::new(level_assets.pattern_weights.iter().map(|(_, w)| *w))
+    else {
+        return;
+    };
+    let Ok(kind_weights) = WeightedIndex::new(level_assets.obstacle_kinds.iter().map(|(_, w)| *w))
+    else {
+        return;
+    };
+
+    // Layouts only depend on the seed and the car, not on how it's driven, so
+    // the car is taken to be two screens behind the pattern going flat out.
+    // Where it really meets moving traffic varies with how it's driven, which
+    // makes the check an estimate; `autopilot_clears_every_level` sweeps seeds
+    // for layouts it gets wrong.
+    let stats = selected.car().stats;
+    let car_x = spawner.next_x - 2.0 * WORLD_WIDTH;
+    let meeting = |obstacle: &Obstacle, translation: Vec3| {
+        obstacle
+            .footprint(translation)
+            .at_meeting(obstacle.speed, car_x, stats.max_speed)
+    };
+    let car_size = stats.size();
+    let lanes: Vec<f32> = (0..LANES)
+        .map(|lane| lane_y(&level_assets.y_values, lane))
+        .collect();
+    spawner
+        .planned
+        .retain(|footprint| footprint.x > car_x - WORLD_WIDTH / 2.0);
+    spawner.patterns += 1;
+    let mut rng = spawner.rng(spawner.patterns);
+
+    for _ in 0..PATTERN_ATTEMPTS {
+        let index = pattern_weights.sample(&mut rng);
+        let Some(pattern) = find_pattern(level_assets.pattern_weights[index].0) else {
+            continue;
+        };
+
+        let mut candidate = Vec::new();
+        for slot in pattern.slots {
+            let kind = level_assets.obstacle_kinds[kind_weights.sample(&mut rng)].0;
+            candidate.push(build_obstacle(
+                kind,
+                spawner.next_x + slot.x,
+                slot.lane,
+                &lanes,
+                &level_assets,
+            ));
+        }
 
-    let mut rng = thread_rng();
-    let x_pos = rand::random::<f32>() * width + level_assets.obstacle_width / 2.0 + offset;
-    let parity = (-1.0_f32).powi(rng.gen_range(0..10));
-    let y_pos = parity * level_assets.y_values[rng.gen_range(0..4)] / window_scale;
-    let speed = parity * level_assets.obstacle_speed;
+        let mut footprints = spawner.planned.clone();
+        footprints.extend(
+            candidate
+                .iter()
+                .map(|(obstacle, translation, _, _)| meeting(obstacle, *translation)),
+        );
+        if !is_passable(
+            &footprints,
+            &lanes,
+            car_size,
+            stats.lateral_speed / stats.max_speed,
+        ) {
+            continue;
+        }
 
-    if obstacles.iter().any(|o| {
-        (o.translation.x - x_pos).abs() < level_assets.obstacle_width
-            && (o.translation.y - y_pos).abs() < level_assets.obstacle_height
-    }) {
+        spawner.planned = footprints;
+        for (obstacle, translation, texture, sheet) in candidate {
+            let mut entity = commands.spawn((
+                SpriteBundle {
+                    sprite: Sprite {
+                        custom_size: Some(obstacle.size),
+                        ..default()
+                    },
+                    texture,
+                    transform: Transform::from_translation(translation),
+                    ..default()
+                },
+                obstacle,
+                LevelAssetMarker,
+            ));
+            if let Some(sheet) = sheet {
+                entity.insert(SpriteAnimator::new(sheet));
+            }
+        }
+        spawner.next_x += pattern.length;
         return;
     }
 
-    commands.spawn((
-        Obstacle { speed },
-        SpriteBundle {
-            sprite: Sprite {
-                custom_size: Some(Vec2 {
-                    x: level_assets.obstacle_width / window_scale,
-                    y: level_assets.obstacle_height / window_scale,
-                }),
-                ..default()
-            },
-            texture: match parity.round() {
-                -1.0 => level_assets.obstacle_texture[1].clone(),
-                _ => level_assets.obstacle_texture[0].clone(),
-            },
-            transform: Transform::from_xyz(x_pos, y_pos, 1.0),
-            ..default()
-        },
-        LevelAssetMarker
-    ));
+    // Nothing fit behind the current obstacles, leave an empty stretch instead.
+    spawner.next_x += WORLD_WIDTH / 2.0;
 }
 
+/// Nearest lane to the car that nothing in `footprints` blocks between the
+/// back of the car and `lookahead` ahead of it.
+pub fn safe_lane(
+    car_pos: Vec3,
+    car_size: Vec2,
+    lanes: &[f32],
+    footprints: &[Footprint],
+    lookahead: f32,
+) -> Option<f32> {
+    lanes
+        .iter()
+        .copied()
+        .filter(|lane| {
+            !footprints.iter().any(|footprint| {
+                footprint.x + footprint.size.x / 2.0 > car_pos.x - car_size.x / 2.0
+                    && footprint.x - footprint.size.x / 2.0 < car_pos.x + lookahead
+                    && (footprint.y - lane).abs() < (footprint.size.y + car_size.y) / 2.0
+            })
+        })
+        .min_by(|a, b| (a - car_pos.y).abs().total_cmp(&(b - car_pos.y).abs()))
+}
+
+#[allow(clippy::too_many_arguments)]
 pub fn detect_collision(
     mut commands: Commands,
     mut car: Query<(&mut Car, &Transform), Without<Obstacle>>,
-    obstacles: Query<(Entity, &Transform), (With<Obstacle>, Without<Car>)>,
+    obstacles: Query<(Entity, &Obstacle, &Transform), Without<Car>>,
     asset_server: Res<AssetServer>,
-    window: Query<&Window>,
-    level_assets: ResMut<LevelAssets>,
+    level_assets: Res<LevelAssets>,
+    recovery: Res<CrashRecovery>,
+    mut power_ups: ResMut<PowerUps>,
+    mut boost: ResMut<Boost>,
+    mut run: ResMut<LevelRun>,
 ) {
-    let window_scale = 1080.0 / window.single().height();
-    let car_pos = car.single().1.translation;
-
-    for obstacle in obstacles.iter() {
-        if (obstacle.1.translation.x - car_pos.x).abs()
-            <= 0.95 * ((CARWIDTH + level_assets.obstacle_width) / window_scale) / 2.0
-            && (obstacle.1.translation.y - car_pos.y).abs()
-                <= 0.90 * ((CARHEIGHT + level_assets.obstacle_height) / window_scale) / 2.0
-        {
-            car.single_mut().0.state = CarState::Crashed;
-            commands.entity(obstacle.0).despawn();
-            commands.spawn(AudioBundle {
-                source: asset_server.load("crash.wav"),
-                settings: PlaybackSettings::DESPAWN,
-            });
-            car.single_mut().0.collision_counter += 1;
+    let (mut car, car_transform) = car.single_mut();
+    if !car.can_crash() {
+        return;
+    }
+    let car_pos = car_transform.translation;
+    let car_size = car.stats.size();
+
+    // Only the first obstacle of a pile-up counts, invulnerability covers the rest.
+    let Some((obstacle_entity, obstacle, _)) =
+        obstacles.iter().find(|(_, obstacle, obstacle_transform)| {
+            (obstacle_transform.translation.x - car_pos.x).abs()
+                <= 0.95 * (car_size.x + obstacle.size.x) / 2.0
+                && (obstacle_transform.translation.y - car_pos.y).abs()
+                    <= 0.90 * (car_size.y + obstacle.size.y) / 2.0
+        })
+    else {
+        return;
+    };
+
+    commands.entity(obstacle_entity).despawn();
+    car.invulnerability = Some(Timer::from_seconds(
+        recovery.invulnerable_seconds,
+        TimerMode::Once,
+    ));
+    if power_ups.absorb_hit() {
+        return;
+    }
+
+    car.state = CarState::Crashed;
+    run.crashes += 1;
+    commands.spawn(AudioBundle {
+        source: asset_server.load("crash.wav"),
+        settings: PlaybackSettings::DESPAWN,
+    });
+    car.health -= crash_damage(
+        obstacle.kind,
+        car.speed.x,
+        car.stats.max_speed,
+        boost.active,
+    );
+    boost.meter = 0.0;
+    boost.active = false;
+
+    let lanes: Vec<f32> = (0..LANES)
+        .map(|lane| lane_y(&level_assets.y_values, lane))
+        .collect();
+    let footprints: Vec<Footprint> = obstacles
+        .iter()
+        .filter(|(entity, _, _)| *entity != obstacle_entity)
+        .map(|(_, obstacle, transform)| obstacle.footprint(transform.translation))
+        .collect();
+    car.knockback_y = safe_lane(car_pos, car_size, &lanes, &footprints, WORLD_WIDTH / 2.0);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{DEFAULT_STATS, MAX_HEALTH};
+
+    #[test]
+    fn seeded_patterns_only_depend_on_seed_level_and_index() {
+        let draw = |seed, level, stream| {
+            let mut spawner = ObstacleSpawner::seeded(Some(seed));
+            spawner.reseed(level);
+            spawner.rng(stream).gen::<u64>()
+        };
+        assert_eq!(draw(7, 2, 3), draw(7, 2, 3));
+        assert_ne!(draw(7, 2, 3), draw(7, 2, 4));
+        assert_ne!(draw(7, 2, 3), draw(7, 3, 3));
+        assert_ne!(draw(7, 2, 3), draw(8, 2, 3));
+    }
+
+    fn crash_app() -> App {
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, AssetPlugin::default()))
+            .init_asset::<AudioSource>()
+            .init_resource::<LevelAssets>()
+            .init_resource::<CrashRecovery>()
+            .init_resource::<PowerUps>()
+            .init_resource::<Boost>()
+            .init_resource::<LevelRun>()
+            .add_systems(Update, detect_collision);
+        app.world_mut()
+            .spawn((Car::default(), Transform::default()));
+        app
+    }
+
+    fn spawn_pile_up(app: &mut App, count: usize) {
+        for n in 0..count {
+            app.world_mut().spawn((
+                Obstacle {
+                    speed: 0.0,
+                    size: Vec2 { x: 90.0, y: 70.0 },
+                    kind: ObstacleKind::Cruiser,
+                    behaviour: ObstacleBehaviour::Cruise,
+                },
+                Transform::from_xyz(n as f32 * 5.0, 0.0, 1.0),
+            ));
+        }
+    }
+
+    #[test]
+    fn braking_trucks_move_on_still_levels() {
+        let level_assets = LevelAssets {
+            obstacle_speed: 0.0,
+            obstacle_texture: vec![Handle::default(), Handle::default()],
+            ..default()
+        };
+        let lanes: Vec<f32> = (0..LANES).map(|lane| lane as f32 * 100.0).collect();
+        for (lane, sign) in [(1, 1.0), (5, -1.0)] {
+            let (truck, ..) =
+                build_obstacle(ObstacleKind::BrakingTruck, 0.0, lane, &lanes, &level_assets);
+            assert_eq!(truck.speed, sign * TRUCK_MIN_SPEED);
+        }
+    }
+
+    fn car(app: &mut App) -> &mut Car {
+        app.world_mut()
+            .query::<&mut Car>()
+            .single_mut(app.world_mut())
+            .into_inner()
+    }
+
+    fn obstacle_count(app: &mut App) -> usize {
+        app.world_mut()
+            .query::<&Obstacle>()
+            .iter(app.world())
+            .count()
+    }
+
+    #[test]
+    fn pile_up_costs_one_crash() {
+        let mut app = crash_app();
+        spawn_pile_up(&mut app, 4);
+
+        for _ in 0..10 {
+            app.update();
+        }
+
+        let one_crash = crash_damage(ObstacleKind::Cruiser, 0.0, DEFAULT_STATS.max_speed, false);
+        assert_eq!(car(&mut app).health, MAX_HEALTH - one_crash);
+        assert_eq!(obstacle_count(&mut app), 3);
+    }
+
+    #[test]
+    fn invulnerable_car_ignores_obstacles_after_recovering() {
+        let mut app = crash_app();
+        spawn_pile_up(&mut app, 2);
+        app.update();
+
+        // The spin ends well before the invulnerability window does.
+        car(&mut app).state = CarState::Moving;
+        for _ in 0..10 {
+            app.update();
         }
+
+        let one_crash = crash_damage(ObstacleKind::Cruiser, 0.0, DEFAULT_STATS.max_speed, false);
+        assert_eq!(car(&mut app).health, MAX_HEALTH - one_crash);
+        assert_eq!(obstacle_count(&mut app), 1);
+    }
+
+    #[test]
+    fn crash_counts_again_once_invulnerability_ends() {
+        let mut app = crash_app();
+        spawn_pile_up(&mut app, 2);
+        app.update();
+
+        car(&mut app).state = CarState::Moving;
+        car(&mut app).invulnerability = None;
+        app.update();
+
+        let one_crash = crash_damage(ObstacleKind::Cruiser, 0.0, DEFAULT_STATS.max_speed, false);
+        assert_eq!(car(&mut app).health, MAX_HEALTH - 2.0 * one_crash);
+        assert_eq!(obstacle_count(&mut app), 0);
+    }
+
+    #[test]
+    fn shield_absorbs_a_pile_up_without_damage() {
+        let mut app = crash_app();
+        app.world_mut().resource_mut::<PowerUps>().shield =
+            Some(Timer::from_seconds(8.0, TimerMode::Once));
+        spawn_pile_up(&mut app, 3);
+
+        for _ in 0..10 {
+            app.update();
+        }
+
+        assert_eq!(car(&mut app).health, MAX_HEALTH);
+        assert!(car(&mut app).state == CarState::Moving);
+        assert_eq!(obstacle_count(&mut app), 2);
+    }
+
+    #[test]
+    fn knockback_picks_the_nearest_clear_lane() {
+        let lanes = [200.0, 100.0, -100.0, -200.0];
+        let blocked = [Footprint {
+            x: 100.0,
+            y: 100.0,
+            size: Vec2 { x: 90.0, y: 70.0 },
+        }];
+        let lane = safe_lane(
+            Vec3::new(0.0, 90.0, 1.0),
+            Vec2 { x: 90.0, y: 70.0 },
+            &lanes,
+            &blocked,
+            400.0,
+        );
+        assert_eq!(lane, Some(200.0));
     }
 }
@@ -0,0 +1,252 @@
+use bevy::prelude::*;
+
+pub const LANES: usize = 8;
+
+// Pattern coordinates are in world units, like the sizes in `LevelAssets`.
+// Lanes run top to bottom; lanes 0..4 travel with the player, 4..8 are oncoming.
+pub struct PatternSlot {
+    pub x: f32,
+    pub lane: usize,
+}
+
+pub struct ObstaclePattern {
+    pub name: &'static str,
+    pub length: f32,
+    pub slots: &'static [PatternSlot],
+}
+
+const fn slot(x: f32, lane: usize) -> PatternSlot {
+    PatternSlot { x, lane }
+}
+
+pub const PATTERNS: &[ObstaclePattern] = &[
+    ObstaclePattern {
+        name: "single",
+        length: 300.0,
+        slots: &[slot(0.0, 2)],
+    },
+    ObstaclePattern {
+        name: "gap",
+        length: 400.0,
+        slots: &[
+            slot(0.0, 0),
+            slot(0.0, 1),
+            slot(0.0, 4),
+            slot(0.0, 5),
+            slot(0.0, 6),
+            slot(0.0, 7),
+        ],
+    },
+    ObstaclePattern {
+        name: "zigzag",
+        length: 1600.0,
+        slots: &[
+            slot(0.0, 1),
+            slot(400.0, 5),
+            slot(800.0, 2),
+            slot(1200.0, 6),
+        ],
+    },
+    ObstaclePattern {
+        name: "convoy",
+        length: 1000.0,
+        slots: &[
+            slot(0.0, 1),
+            slot(220.0, 1),
+            slot(440.0, 1),
+            slot(660.0, 1),
+            slot(100.0, 6),
+            slot(320.0, 6),
+            slot(540.0, 6),
+        ],
+    },
+    ObstaclePattern {
+        name: "chicane",
+        length: 1400.0,
+        slots: &[
+            slot(0.0, 0),
+            slot(0.0, 1),
+            slot(0.0, 2),
+            slot(0.0, 3),
+            slot(900.0, 4),
+            slot(900.0, 5),
+            slot(900.0, 6),
+            slot(900.0, 7),
+        ],
+    },
+];
+
+pub fn find_pattern(name: &str) -> Option<&'static ObstaclePattern> {
+    PATTERNS.iter().find(|pattern| pattern.name == name)
+}
+
+pub fn lane_y(y_values: &[f32; 4], lane: usize) -> f32 {
+    if lane < 4 {
+        y_values[3 - lane]
+    } else {
+        -y_values[lane - 4]
+    }
+}
+
+/// World space rectangle occupied by an obstacle, used by the passable gap check.
+#[derive(Clone, Copy, Debug)]
+pub struct Footprint {
+    pub x: f32,
+    pub y: f32,
+    pub size: Vec2,
+}
+
+impl Footprint {
+    /// Roughly where the footprint of something going `speed` along the road
+    /// will be once a car at `car_x` going `car_speed` catches up with it.
+    /// Traffic in opposite directions closes up gaps on the way, so gaps are
+    /// checked against these rather than where things were spawned. A car that
+    /// is slower or further back than assumed meets it somewhere else, so a gap
+    /// that passes here is likely but not certain to be open in play.
+    pub fn at_meeting(mut self, speed: f32, car_x: f32, car_speed: f32) -> Footprint {
+        let closing = car_speed - speed;
+        if closing > 0.0 {
+            self.x += speed * (self.x - car_x).max(0.0) / closing;
+        }
+        self
+    }
+}
+
+struct Column {
+    start: f32,
+    end: f32,
+    obstacles: Vec<Footprint>,
+}
+
+/// Returns true if a car of `car_size` can thread every obstacle in `footprints`
+/// by sitting in one of `lanes` at each wall and changing lanes in between,
+/// once it is clear of one wall and before it reaches the next.
+/// `lateral_ratio` is how far the car moves sideways per unit driven forward.
+pub fn is_passable(
+    footprints: &[Footprint],
+    lanes: &[f32],
+    car_size: Vec2,
+    lateral_ratio: f32,
+) -> bool {
+    let mut sorted = footprints.to_vec();
+    sorted.sort_by(|a, b| a.x.total_cmp(&b.x));
+
+    // Obstacles closer together than the car is long form a single wall.
+    let mut columns: Vec<Column> = Vec::new();
+    for footprint in sorted {
+        let start = footprint.x - footprint.size.x / 2.0;
+        let end = footprint.x + footprint.size.x / 2.0;
+        match columns.last_mut() {
+            Some(column) if start - column.end < car_size.x => {
+                column.end = column.end.max(end);
+                column.obstacles.push(footprint);
+            }
+            _ => columns.push(Column {
+                start,
+                end,
+                obstacles: vec![footprint],
+            }),
+        }
+    }
+
+    let mut reachable: Vec<f32> = lanes.to_vec();
+    let mut previous_end: Option<f32> = None;
+    for column in columns {
+        let free: Vec<f32> = lanes
+            .iter()
+            .copied()
+            .filter(|lane| {
+                !column.obstacles.iter().any(|obstacle| {
+                    (obstacle.y - lane).abs() <= 0.90 * (obstacle.size.y + car_size.y) / 2.0
+                })
+            })
+            .collect();
+
+        reachable = match previous_end {
+            None => free,
+            Some(end) => {
+                let reach = (column.start - end - car_size.x).max(0.0) * lateral_ratio;
+                free.into_iter()
+                    .filter(|lane| reachable.iter().any(|from| (lane - from).abs() <= reach))
+                    .collect()
+            }
+        };
+        if reachable.is_empty() {
+            return false;
+        }
+        previous_end = Some(column.end);
+    }
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const LANE_YS: [f32; 4] = [0.0, 150.0, 300.0, 450.0];
+    const CAR: Vec2 = Vec2::new(135.0, 105.0);
+
+    fn block(x: f32, y: f32) -> Footprint {
+        Footprint { x, y, size: CAR }
+    }
+
+    /// Blocks every lane at `x` except `open`.
+    fn wall(x: f32, open: f32) -> Vec<Footprint> {
+        LANE_YS
+            .iter()
+            .filter(|y| **y != open)
+            .map(|y| block(x, *y))
+            .collect()
+    }
+
+    #[test]
+    fn empty_road_is_passable() {
+        assert!(is_passable(&[], &LANE_YS, CAR, 0.5));
+    }
+
+    #[test]
+    fn full_wall_is_impassable() {
+        let footprints: Vec<Footprint> = LANE_YS.iter().map(|y| block(0.0, *y)).collect();
+        assert!(!is_passable(&footprints, &LANE_YS, CAR, 0.5));
+    }
+
+    #[test]
+    fn close_obstacles_form_one_wall() {
+        // Each lane is open at one of the two x positions, but they are closer
+        // together than the car is long, so there is no way between them.
+        let mut footprints = wall(0.0, 0.0);
+        footprints.push(block(100.0, 0.0));
+        assert!(!is_passable(&footprints, &LANE_YS, CAR, 0.5));
+    }
+
+    #[test]
+    fn opposing_traffic_closes_gaps() {
+        // Open at opposite edges, with just enough room between the walls as
+        // spawned. The first wall drives away from the car and the second
+        // towards it, so they are much closer by the time it gets there.
+        let mut footprints = wall(0.0, 0.0);
+        footprints.extend(wall(1250.0, 450.0));
+        assert!(is_passable(&footprints, &LANE_YS, CAR, 0.5));
+
+        let met: Vec<Footprint> = footprints
+            .iter()
+            .map(|footprint| {
+                let speed = if footprint.x < 500.0 { 100.0 } else { -100.0 };
+                footprint.at_meeting(speed, -2000.0, 1100.0)
+            })
+            .collect();
+        assert!(!is_passable(&met, &LANE_YS, CAR, 0.5));
+    }
+
+    #[test]
+    fn lane_changes_need_room() {
+        // Walls open at opposite edges of the road, 450 apart sideways.
+        let mut footprints = wall(0.0, 0.0);
+        footprints.extend(wall(850.0, 450.0));
+        // 715 between the walls less the car's length leaves 580 to steer
+        // in, which at 0.5 sideways per unit forward is 290.
+        assert!(!is_passable(&footprints, &LANE_YS, CAR, 0.5));
+        // At 1.0 it's 580, enough to get across.
+        assert!(is_passable(&footprints, &LANE_YS, CAR, 1.0));
+    }
+}
@@ -0,0 +1,274 @@
+use std::time::Duration;
+
+use bevy::prelude::*;
+use rand::distributions::WeightedIndex;
+use rand::prelude::*;
+
+use rand::rngs::StdRng;
+
+use crate::{
+    lane_y, CameraMarker, Car, Hud, LevelAssetMarker, LevelAssets, Obstacle, ObstacleSpawner,
+    LANES, MAX_HEALTH, PICKUP_STREAM, REPAIR_AMOUNT, WORLD_WIDTH,
+};
+
+const PICKUP_SIZE: f32 = 60.0;
+
+const SHIELD_SECONDS: f32 = 8.0;
+const SLOW_MOTION_SECONDS: f32 = 4.0;
+const SLOW_MOTION_SPEED: f32 = 0.5;
+const MULTIPLIER_SECONDS: f32 = 8.0;
+const SCORE_MULTIPLIER: f32 = 2.0;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PickupKind {
+    Shield,
+    SlowMotion,
+    ScoreMultiplier,
+    Repair,
+}
+
+impl PickupKind {
+    fn color(&self) -> Color {
+        match self {
+            PickupKind::Shield => Color::srgb(0.3, 0.6, 1.0),
+            PickupKind::SlowMotion => Color::srgb(0.3, 1.0, 0.4),
+            PickupKind::ScoreMultiplier => Color::srgb(1.0, 0.85, 0.2),
+            PickupKind::Repair => Color::srgb(1.0, 0.3, 0.3),
+        }
+    }
+}
+
+#[derive(Component)]
+pub struct Pickup {
+    pub kind: PickupKind,
+}
+
+/// Timed effects granted by pickups. Timers run on real time so slow motion
+/// does not stretch its own duration.
+#[derive(Resource, Default)]
+pub struct PowerUps {
+    pub shield: Option<Timer>,
+    pub slow_motion: Option<Timer>,
+    pub multiplier: Option<Timer>,
+}
+
+impl PowerUps {
+    pub fn score_multiplier(&self) -> f32 {
+        match self.multiplier {
+            Some(_) => SCORE_MULTIPLIER,
+            None => 1.0,
+        }
+    }
+
+    /// Uses up the shield if one is active, returning whether it absorbed the hit.
+    pub fn absorb_hit(&mut self) -> bool {
+        self.shield.take().is_some()
+    }
+
+    /// Active effects and their remaining time, as shown on the HUD.
+    pub fn hud_text(&self) -> String {
+        let mut timers = Vec::new();
+        for (label, timer) in [
+            ("SHIELD", &self.shield),
+            ("SLOW", &self.slow_motion),
+            ("BONUS", &self.multiplier),
+        ] {
+            if let Some(timer) = timer {
+                timers.push(format!("{} {:.1}", label, timer.remaining_secs()));
+            }
+        }
+        timers.join("  ")
+    }
+}
+
+/// Places pickups with draws of their own, so how often obstacles get
+/// redrawn never changes where the pickups go.
+#[derive(Resource)]
+pub struct PickupSpawner {
+    pub next_x: f32,
+    rng: StdRng,
+}
+
+impl Default for PickupSpawner {
+    fn default() -> Self {
+        PickupSpawner {
+            next_x: 0.0,
+            rng: StdRng::from_entropy(),
+        }
+    }
+}
+
+pub fn reset_power_ups(
+    mut power_ups: ResMut<PowerUps>,
+    mut pickup_spawner: ResMut<PickupSpawner>,
+    spawner: Res<ObstacleSpawner>,
+    mut time: ResMut<Time<Virtual>>,
+) {
+    *power_ups = PowerUps::default();
+    pickup_spawner.rng = spawner.rng(PICKUP_STREAM);
+    pickup_spawner.next_x = WORLD_WIDTH + WORLD_WIDTH * pickup_spawner.rng.gen_range(1.0..3.0);
+    time.set_relative_speed(1.0);
+}
+
+pub fn spawn_pickups(
+    mut commands: Commands,
+    obstacles: Query<(&Obstacle, &Transform), Without<CameraMarker>>,
+    camera: Query<&Transform, (With<CameraMarker>, Without<Obstacle>)>,
+    level_assets: Res<LevelAssets>,
+    spawner: Res<ObstacleSpawner>,
+    mut pickup_spawner: ResMut<PickupSpawner>,
+) {
+    let camera_x = camera.single().translation.x;
+
+    pickup_spawner.next_x = pickup_spawner.next_x.max(camera_x + WORLD_WIDTH);
+    // Wait until obstacles have been placed this far so the pickup can dodge them.
+    if pickup_spawner.next_x > spawner.next_x {
+        return;
+    }
+
+    let Ok(weights) = WeightedIndex::new(level_assets.pickup_kinds.iter().map(|(_, w)| *w)) else {
+        return;
+    };
+    let kind = level_assets.pickup_kinds[weights.sample(&mut pickup_spawner.rng)].0;
+
+    let size = PICKUP_SIZE;
+    let x = pickup_spawner.next_x;
+    let mut lanes: Vec<usize> = (0..LANES).collect();
+    lanes.shuffle(&mut pickup_spawner.rng);
+    let lane = lanes.into_iter().find(|lane| {
+        let y = lane_y(&level_assets.y_values, *lane);
+        !obstacles.iter().any(|(obstacle, transform)| {
+            let footprint = obstacle.footprint(transform.translation);
+            (footprint.x - x).abs() <= (footprint.size.x + size) / 2.0
+                && (footprint.y - y).abs() <= (footprint.size.y + size) / 2.0
+        })
+    });
+
+    if let Some(lane) = lane {
+        commands.spawn((
+            SpriteBundle {
+                sprite: Sprite {
+                    color: kind.color(),
+                    custom_size: Some(Vec2::splat(size)),
+                    ..default()
+                },
+                transform: Transform::from_xyz(x, lane_y(&level_assets.y_values, lane), 1.0),
+                ..default()
+            },
+            Pickup { kind },
+            LevelAssetMarker,
+        ));
+    }
+    pickup_spawner.next_x = x + WORLD_WIDTH * pickup_spawner.rng.gen_range(1.0..3.0);
+}
+
+pub fn collect_pickups(
+    mut commands: Commands,
+    mut car: Query<(&mut Car, &Transform), Without<Pickup>>,
+    pickups: Query<(Entity, &Pickup, &Transform), Without<Car>>,
+    camera: Query<&Transform, (With<CameraMarker>, Without<Pickup>)>,
+    mut power_ups: ResMut<PowerUps>,
+    mut time: ResMut<Time<Virtual>>,
+) {
+    let car_pos = car.single().1.translation;
+    let car_size = car.single().0.stats.size();
+
+    for (pickup_entity, pickup, pickup_transform) in pickups.iter() {
+        if pickup_transform.translation.x < camera.single().translation.x - WORLD_WIDTH / 2.0 {
+            commands.entity(pickup_entity).despawn();
+            continue;
+        }
+
+        if (pickup_transform.translation.x - car_pos.x).abs() <= (car_size.x + PICKUP_SIZE) / 2.0
+            && (pickup_transform.translation.y - car_pos.y).abs()
+                <= (car_size.y + PICKUP_SIZE) / 2.0
+        {
+            commands.entity(pickup_entity).despawn();
+            match pickup.kind {
+                PickupKind::Shield => {
+                    power_ups.shield = Some(Timer::from_seconds(SHIELD_SECONDS, TimerMode::Once));
+                }
+                PickupKind::SlowMotion => {
+                    power_ups.slow_motion =
+                        Some(Timer::from_seconds(SLOW_MOTION_SECONDS, TimerMode::Once));
+                    time.set_relative_speed(SLOW_MOTION_SPEED);
+                }
+                PickupKind::ScoreMultiplier => {
+                    power_ups.multiplier =
+                        Some(Timer::from_seconds(MULTIPLIER_SECONDS, TimerMode::Once));
+                }
+                PickupKind::Repair => {
+                    let mut car = car.single_mut().0;
+                    car.health = (car.health + REPAIR_AMOUNT).min(MAX_HEALTH);
+                }
+            }
+        }
+    }
+}
+
+/// Ticks the effects without flagging a change, unless the HUD would show
+/// something different.
+pub fn update_power_ups(
+    real_time: Res<Time<Real>>,
+    mut time: ResMut<Time<Virtual>>,
+    mut power_ups: ResMut<PowerUps>,
+) {
+    let shown = power_ups.hud_text();
+    let effects = power_ups.bypass_change_detection();
+    let delta = real_time.delta();
+    let expired = |timer: &mut Option<Timer>, delta: Duration| {
+        if let Some(t) = timer {
+            if t.tick(delta).finished() {
+                *timer = None;
+            }
+        }
+    };
+
+    expired(&mut effects.shield, delta);
+    expired(&mut effects.multiplier, delta);
+    if effects.slow_motion.is_some() {
+        expired(&mut effects.slow_motion, delta);
+        if effects.slow_motion.is_none() {
+            time.set_relative_speed(1.0);
+        }
+    }
+    if effects.hud_text() != shown {
+        power_ups.set_changed();
+    }
+}
+
+#[derive(Component)]
+pub struct PowerUpText;
+
+pub fn spawn_power_up_hud(mut commands: Commands, asset_server: Res<AssetServer>) {
+    commands.spawn((
+        TextBundle::from_section(
+            "",
+            TextStyle {
+                font_size: 40.0,
+                font: asset_server.load("GohuFont11NerdFont-Regular.ttf"),
+                ..default()
+            },
+        )
+        .with_text_justify(JustifyText::Center)
+        .with_style(Style {
+            position_type: PositionType::Absolute,
+            top: Val::Percent(50.0 / 1080.0 * 100.0),
+            left: Val::Percent(25.0),
+            width: Val::Percent(50.0),
+            ..default()
+        }),
+        PowerUpText,
+        Hud,
+    ));
+}
+
+pub fn update_power_up_hud(
+    power_ups: Res<PowerUps>,
+    mut text: Query<&mut Text, With<PowerUpText>>,
+) {
+    if !power_ups.is_changed() {
+        return;
+    }
+    text.single_mut().sections[0].value = power_ups.hud_text();
+}
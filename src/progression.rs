@@ -0,0 +1,222 @@
+use bevy::prelude::*;
+
+use crate::{SaveData, Score, ROSTER};
+
+const NOTICE_SECONDS: f32 = 3.0;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Unlockable {
+    Level(u8),
+    /// Index into `ROSTER`.
+    Car(usize),
+}
+
+impl Unlockable {
+    pub fn key(&self) -> String {
+        match self {
+            Unlockable::Level(level) => format!("level:{}", level),
+            Unlockable::Car(index) => format!("car:{}", index),
+        }
+    }
+
+    /// Inverse of `key`, ignoring anything no longer present in `UNLOCKS`.
+    pub fn from_key(key: &str) -> Option<Self> {
+        let (kind, value) = key.trim().split_once(':')?;
+        let unlockable = match kind {
+            "level" => Unlockable::Level(value.parse().ok()?),
+            "car" => Unlockable::Car(value.parse().ok()?),
+            _ => return None,
+        };
+        UNLOCKS
+            .iter()
+            .any(|rule| rule.unlock == unlockable)
+            .then_some(unlockable)
+    }
+
+    pub fn name(&self) -> String {
+        match self {
+            Unlockable::Level(level) => format!("LEVEL {}", level),
+            Unlockable::Car(index) => ROSTER[*index].name.to_string(),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum UnlockCondition {
+    Score(u32),
+    Clear(u8),
+    /// Clear the level without a single crash; shielded hits don't count.
+    CleanClear(u8),
+}
+
+impl UnlockCondition {
+    pub fn met(&self, save: &SaveData) -> bool {
+        match self {
+            UnlockCondition::Score(score) => save.high_score >= *score,
+            UnlockCondition::Clear(level) => save.level(*level).cleared,
+            UnlockCondition::CleanClear(level) => save.level(*level).clean,
+        }
+    }
+
+    pub fn describe(&self) -> String {
+        match self {
+            UnlockCondition::Score(score) => format!("SCORE {}", score),
+            UnlockCondition::Clear(level) => format!("CLEAR LEVEL {}", level),
+            UnlockCondition::CleanClear(level) => {
+                format!("CLEAR LEVEL {} WITHOUT CRASHING", level)
+            }
+        }
+    }
+}
+
+pub struct UnlockRule {
+    pub unlock: Unlockable,
+    pub condition: UnlockCondition,
+}
+
+/// Everything that starts locked. Anything not listed here is always available.
+pub const UNLOCKS: &[UnlockRule] = &[
+    UnlockRule {
+        unlock: Unlockable::Level(2),
+        condition: UnlockCondition::Clear(1),
+    },
+    UnlockRule {
+        unlock: Unlockable::Level(3),
+        condition: UnlockCondition::Clear(2),
+    },
+    UnlockRule {
+        unlock: Unlockable::Car(1),
+        condition: UnlockCondition::Score(500),
+    },
+    UnlockRule {
+        unlock: Unlockable::Car(2),
+        condition: UnlockCondition::CleanClear(2),
+    },
+];
+
+pub fn unlock_rule(unlock: Unlockable) -> Option<&'static UnlockRule> {
+    UNLOCKS.iter().find(|rule| rule.unlock == unlock)
+}
+
+pub fn is_unlocked(unlock: Unlockable, save: &SaveData) -> bool {
+    unlock_rule(unlock).is_none() || save.unlocked.contains(&unlock)
+}
+
+/// Rules whose condition is met but haven't been recorded in `save` yet.
+pub fn pending_unlocks(save: &SaveData) -> Vec<Unlockable> {
+    UNLOCKS
+        .iter()
+        .filter(|rule| !save.unlocked.contains(&rule.unlock) && rule.condition.met(save))
+        .map(|rule| rule.unlock)
+        .collect()
+}
+
+/// Crashes and starting score for the level being driven, used to judge clears.
+#[derive(Resource, Default)]
+pub struct LevelRun {
+    pub crashes: u32,
+    pub start_score: f32,
+}
+
+pub fn reset_level_run(mut run: ResMut<LevelRun>, score: Res<Score>) {
+    *run = LevelRun {
+        crashes: 0,
+        start_score: score.score,
+    };
+}
+
+#[derive(Component)]
+pub struct UnlockNotice {
+    timer: Timer,
+}
+
+pub fn check_unlocks(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    mut save: ResMut<SaveData>,
+    notices: Query<&UnlockNotice>,
+) {
+    if !save.is_changed() {
+        return;
+    }
+    let unlocks = pending_unlocks(&save);
+    if unlocks.is_empty() {
+        return;
+    }
+
+    let shown = notices.iter().count();
+    for (n, unlock) in unlocks.into_iter().enumerate() {
+        save.unlocked.push(unlock);
+        commands.spawn((
+            TextBundle::from_section(
+                format!("UNLOCKED {}", unlock.name()),
+                TextStyle {
+                    font_size: 40.0,
+                    font: asset_server.load("GohuFont11NerdFont-Regular.ttf"),
+                    color: Color::srgb(1.0, 0.85, 0.2),
+                },
+            )
+            .with_text_justify(JustifyText::Center)
+            .with_style(Style {
+                position_type: PositionType::Absolute,
+                top: Val::Percent(25.0 + 5.0 * (shown + n) as f32),
+                left: Val::Percent(25.0),
+                width: Val::Percent(50.0),
+                ..default()
+            }),
+            UnlockNotice {
+                timer: Timer::from_seconds(NOTICE_SECONDS, TimerMode::Once),
+            },
+        ));
+    }
+    save.store();
+}
+
+pub fn update_unlock_notices(
+    mut commands: Commands,
+    time: Res<Time<Real>>,
+    mut notices: Query<(Entity, &mut UnlockNotice)>,
+) {
+    for (entity, mut notice) in notices.iter_mut() {
+        if notice.timer.tick(time.delta()).finished() {
+            commands.entity(entity).despawn();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn clean_clear_unlocks_once() {
+        let mut save = SaveData::default();
+        save.record_clear(2, 300, false);
+        assert_eq!(pending_unlocks(&save), vec![Unlockable::Level(3)]);
+
+        save.unlocked.push(Unlockable::Level(3));
+        save.record_clear(2, 200, true);
+        assert_eq!(pending_unlocks(&save), vec![Unlockable::Car(2)]);
+        assert_eq!(save.level(2).best_score, 300);
+    }
+
+    #[test]
+    fn unlocks_without_a_rule_are_dropped() {
+        let save = SaveData::parse("unlocked=level:2,mode:ENDLESS,car:0\n");
+        assert_eq!(save.unlocked, vec![Unlockable::Level(2)]);
+    }
+
+    #[test]
+    fn progress_survives_a_round_trip() {
+        let mut save = SaveData {
+            high_score: 1234,
+            ..default()
+        };
+        save.record_clear(1, 800, true);
+        save.unlocked = pending_unlocks(&save);
+
+        assert_eq!(SaveData::parse(&save.serialize()), save);
+        assert!(!is_unlocked(Unlockable::Level(3), &save));
+        assert!(is_unlocked(Unlockable::Car(0), &save));
+    }
+}
@@ -0,0 +1,220 @@
+use std::fs;
+use std::path::PathBuf;
+
+use bevy::prelude::*;
+
+use crate::{Car, Level, LevelAssetMarker, MinimapMarker, SelectedCar, LEVELS, ROSTER};
+
+/// Time between recorded car positions; the ghost interpolates in between.
+const SAMPLE_SECONDS: f32 = 0.05;
+const GHOST_COLOR: Color = Color::srgba(0.6, 0.8, 1.0, 0.4);
+
+/// A single drive through one level, stored as `key=value` lines like the save
+/// file. The seed fixes which obstacle patterns and pickups come up, though
+/// traffic that moves still ends up wherever the timing puts it.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Recording {
+    pub level: u8,
+    pub seed: u64,
+    /// Index into `ROSTER`.
+    pub car: usize,
+    /// Car position every `SAMPLE_SECONDS` from the start of the race.
+    pub samples: Vec<Vec2>,
+}
+
+impl Recording {
+    pub fn load(path: &PathBuf) -> Result<Self, String> {
+        let contents = fs::read_to_string(path)
+            .map_err(|error| format!("could not read {}: {}", path.display(), error))?;
+        Recording::parse(&contents).map_err(|error| format!("{}: {}", path.display(), error))
+    }
+
+    pub fn parse(contents: &str) -> Result<Self, String> {
+        let mut recording = Recording::default();
+        for line in contents.lines() {
+            let Some((key, value)) = line.split_once('=') else {
+                continue;
+            };
+            let (key, value) = (key.trim(), value.trim());
+            let invalid = || format!("invalid {} '{}'", key, value);
+            match key {
+                "level" => recording.level = value.parse().map_err(|_| invalid())?,
+                "seed" => recording.seed = value.parse().map_err(|_| invalid())?,
+                "car" => recording.car = value.parse().map_err(|_| invalid())?,
+                "sample" => {
+                    let (x, y) = value.split_once(',').ok_or_else(invalid)?;
+                    recording.samples.push(Vec2::new(
+                        x.trim().parse().map_err(|_| invalid())?,
+                        y.trim().parse().map_err(|_| invalid())?,
+                    ));
+                }
+                _ => {}
+            }
+        }
+        if recording.level == 0 || recording.car >= ROSTER.len() {
+            return Err("not a recording".to_string());
+        }
+        if recording.level as usize > LEVELS.len() {
+            return Err(format!("level must be 1 to {}", LEVELS.len()));
+        }
+        Ok(recording)
+    }
+
+    pub fn serialize(&self) -> String {
+        let mut contents = format!(
+            "level={}\nseed={}\ncar={}\n",
+            self.level, self.seed, self.car
+        );
+        for sample in self.samples.iter() {
+            contents += &format!("sample={},{}\n", sample.x, sample.y);
+        }
+        contents
+    }
+
+    /// Where the car was `time` seconds into the race, holding the last
+    /// position once the recording runs out.
+    pub fn position(&self, time: f32) -> Option<Vec2> {
+        let at = (time / SAMPLE_SECONDS).max(0.0);
+        let index = at as usize;
+        let from = self.samples.get(index).or(self.samples.last())?;
+        let to = self.samples.get(index + 1).unwrap_or(from);
+        Some(from.lerp(*to, at.fract()))
+    }
+}
+
+/// Writes the car's drive to `path` each time the race stops, from `--record`.
+#[derive(Resource)]
+pub struct Recorder {
+    pub path: PathBuf,
+    pub recording: Recording,
+    clock: f32,
+}
+
+impl Recorder {
+    pub fn new(path: PathBuf, seed: u64) -> Self {
+        Recorder {
+            path,
+            recording: Recording { seed, ..default() },
+            clock: 0.0,
+        }
+    }
+}
+
+/// Drive played back as a ghost car, from `--replay`.
+#[derive(Resource)]
+pub struct Replay {
+    pub recording: Recording,
+}
+
+#[derive(Component)]
+pub struct Ghost {
+    clock: f32,
+}
+
+pub fn reset_recorder(
+    level: Res<Level>,
+    selected: Res<SelectedCar>,
+    recorder: Option<ResMut<Recorder>>,
+) {
+    let Some(mut recorder) = recorder else {
+        return;
+    };
+    recorder.recording.level = level.level;
+    recorder.recording.car = selected.index;
+    recorder.recording.samples.clear();
+    recorder.clock = 0.0;
+}
+
+pub fn record_car(
+    time: Res<Time>,
+    car: Query<&Transform, With<Car>>,
+    recorder: Option<ResMut<Recorder>>,
+) {
+    let (Some(mut recorder), Ok(car)) = (recorder, car.get_single()) else {
+        return;
+    };
+    recorder.clock += time.delta_seconds();
+    while recorder.recording.samples.len() as f32 * SAMPLE_SECONDS <= recorder.clock {
+        recorder.recording.samples.push(car.translation.truncate());
+    }
+}
+
+pub fn store_recording(recorder: Option<Res<Recorder>>) {
+    let Some(recorder) = recorder else {
+        return;
+    };
+    if let Err(error) = fs::write(&recorder.path, recorder.recording.serialize()) {
+        warn!("could not write {}: {}", recorder.path.display(), error);
+    }
+}
+
+pub fn spawn_ghost(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    level: Res<Level>,
+    replay: Option<Res<Replay>>,
+    ghost: Query<&Ghost>,
+) {
+    let Some(replay) = replay else {
+        return;
+    };
+    if !ghost.is_empty() || replay.recording.level != level.level {
+        return;
+    }
+    let car = &ROSTER[replay.recording.car];
+    let start = replay.recording.position(0.0).unwrap_or_default();
+
+    commands.spawn((
+        SpriteBundle {
+            sprite: Sprite {
+                custom_size: Some(car.stats.size()),
+                color: GHOST_COLOR,
+                ..default()
+            },
+            texture: asset_server.load(car.texture),
+            transform: Transform::from_translation(start.extend(0.9)),
+            ..default()
+        },
+        Ghost { clock: 0.0 },
+        MinimapMarker {
+            color: Color::srgb(0.6, 0.8, 1.0),
+        },
+        LevelAssetMarker,
+    ));
+}
+
+pub fn update_ghost(
+    time: Res<Time>,
+    replay: Option<Res<Replay>>,
+    mut ghost: Query<(&mut Ghost, &mut Transform)>,
+) {
+    let Some(replay) = replay else {
+        return;
+    };
+    for (mut ghost, mut transform) in ghost.iter_mut() {
+        ghost.clock += time.delta_seconds();
+        if let Some(position) = replay.recording.position(ghost.clock) {
+            transform.translation = position.extend(transform.translation.z);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn recordings_round_trip_and_stay_on_real_levels() {
+        let recording = Recording {
+            level: 2,
+            seed: 42,
+            car: 1,
+            samples: vec![Vec2::new(0.0, 72.5), Vec2::new(55.0, 80.0)],
+        };
+        assert_eq!(Recording::parse(&recording.serialize()), Ok(recording));
+
+        let past_the_last = format!("level={}\nseed=1\ncar=0\n", LEVELS.len() + 1);
+        assert!(Recording::parse(&past_the_last).is_err());
+        assert!(Recording::parse("seed=1\ncar=0\n").is_err());
+    }
+}
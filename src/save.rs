@@ -0,0 +1,129 @@
+use std::env;
+use std::fs;
+use std::path::PathBuf;
+
+use bevy::prelude::*;
+
+use crate::{Score, Unlockable};
+
+const SAVE_FILE: &str = "midnight_motorist.sav";
+
+/// The save file in the executable's directory, or the working directory if
+/// that can't be found.
+fn save_path() -> PathBuf {
+    env::current_exe()
+        .ok()
+        .and_then(|exe| exe.parent().map(|dir| dir.join(SAVE_FILE)))
+        .unwrap_or_else(|| PathBuf::from(SAVE_FILE))
+}
+
+/// Best result on a single level.
+#[derive(Clone, Copy, Default, Debug, PartialEq)]
+pub struct LevelRecord {
+    pub best_score: u32,
+    pub cleared: bool,
+    /// Cleared at least once without crashing.
+    pub clean: bool,
+}
+
+/// Progress kept between runs, stored as `key=value` lines next to the binary.
+#[derive(Resource, Default, Debug, PartialEq)]
+pub struct SaveData {
+    pub high_score: u32,
+    /// Indexed by level number minus one.
+    pub levels: Vec<LevelRecord>,
+    pub unlocked: Vec<Unlockable>,
+}
+
+impl SaveData {
+    pub fn load() -> Self {
+        fs::read_to_string(save_path())
+            .map(|contents| SaveData::parse(&contents))
+            .unwrap_or_default()
+    }
+
+    pub fn parse(contents: &str) -> Self {
+        let mut save = SaveData::default();
+        for line in contents.lines() {
+            let Some((key, value)) = line.split_once('=') else {
+                continue;
+            };
+            let (key, value) = (key.trim(), value.trim());
+            if key == "high_score" {
+                save.high_score = value.parse().unwrap_or_default();
+            } else if key == "unlocked" {
+                save.unlocked = value.split(',').filter_map(Unlockable::from_key).collect();
+            } else if let Some(level) = key.strip_prefix("level.") {
+                let Ok(level @ 1..) = level.parse::<u8>() else {
+                    continue;
+                };
+                let mut fields = value.split(',').map(str::trim);
+                let record = save.level_mut(level);
+                record.best_score = fields
+                    .next()
+                    .and_then(|score| score.parse().ok())
+                    .unwrap_or_default();
+                record.cleared = fields.next() == Some("1");
+                record.clean = fields.next() == Some("1");
+            }
+        }
+        save
+    }
+
+    pub fn serialize(&self) -> String {
+        let mut contents = format!("high_score={}\n", self.high_score);
+        for (index, record) in self.levels.iter().enumerate() {
+            contents += &format!(
+                "level.{}={},{},{}\n",
+                index + 1,
+                record.best_score,
+                record.cleared as u8,
+                record.clean as u8,
+            );
+        }
+        let unlocked: Vec<String> = self.unlocked.iter().map(Unlockable::key).collect();
+        contents += &format!("unlocked={}\n", unlocked.join(","));
+        contents
+    }
+
+    pub fn store(&self) {
+        let path = save_path();
+        if let Err(error) = fs::write(&path, self.serialize()) {
+            warn!("could not write {}: {}", path.display(), error);
+        }
+    }
+
+    pub fn level(&self, level: u8) -> LevelRecord {
+        (level as usize)
+            .checked_sub(1)
+            .and_then(|index| self.levels.get(index))
+            .copied()
+            .unwrap_or_default()
+    }
+
+    fn level_mut(&mut self, level: u8) -> &mut LevelRecord {
+        let index = level as usize - 1;
+        if self.levels.len() <= index {
+            self.levels.resize(index + 1, LevelRecord::default());
+        }
+        &mut self.levels[index]
+    }
+
+    pub fn record_clear(&mut self, level: u8, score: u32, clean: bool) {
+        let record = self.level_mut(level);
+        record.best_score = record.best_score.max(score);
+        record.cleared = true;
+        record.clean |= clean;
+    }
+}
+
+pub fn update_high_score(score: Res<Score>, mut save: ResMut<SaveData>) {
+    let score = score.score.floor() as u32;
+    if score > save.high_score {
+        save.high_score = score;
+    }
+}
+
+pub fn store_save(save: Res<SaveData>) {
+    save.store();
+}
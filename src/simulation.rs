@@ -0,0 +1,244 @@
+use std::time::Duration;
+
+use bevy::ecs::system::RunSystemOnce;
+use bevy::prelude::*;
+use bevy::state::app::StatesPlugin;
+use bevy::time::TimeUpdateStrategy;
+
+use crate::{
+    autopilot_plan, lane_y, load_level, reset_power_ups, spawn_car, update_boost, CameraMarker,
+    Car, GameState, GameplayPlugin, Level, LevelAssets, LevelRun, Obstacle, ObstacleSpawner,
+    SaveData, Score, SelectedCar, LANES,
+};
+
+/// Simulated time per update, matching a 60Hz frame.
+pub const STEP: Duration = Duration::from_nanos(16_666_667);
+/// Time spent in each lane by `Driver::Weave`.
+const WEAVE_SECONDS: f32 = 3.0;
+/// Distance from the target lane the drivers treat as in it.
+const STEER_DEADZONE: f32 = 5.0;
+
+/// Who is at the wheel of a headless run.
+#[derive(Resource, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Driver {
+    /// Never steers, so every obstacle in the starting lane is a crash.
+    Straight,
+    /// Works through the lanes in turn without looking at the road.
+    Weave,
+    /// Plans a way through the traffic ahead, see `autopilot_steer`.
+    Autopilot,
+}
+
+impl Driver {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "straight" => Some(Driver::Straight),
+            "weave" => Some(Driver::Weave),
+            "autopilot" => Some(Driver::Autopilot),
+            _ => None,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Driver::Straight => "straight",
+            Driver::Weave => "weave",
+            Driver::Autopilot => "autopilot",
+        }
+    }
+}
+
+/// Stretches of road where the car found every way ahead blocked, counted
+/// once each however long they last.
+#[derive(Resource, Default)]
+pub struct DeadEnds {
+    pub count: u32,
+    /// Whether the car was in one as of the last update.
+    blocked: bool,
+}
+
+/// Holds up or down on `input` to move a car at `y` towards `target`.
+pub fn steer_towards(input: &mut ButtonInput<KeyCode>, y: f32, target: f32) {
+    if target > y + STEER_DEADZONE {
+        input.press(KeyCode::KeyW);
+    } else if target < y - STEER_DEADZONE {
+        input.press(KeyCode::KeyS);
+    }
+}
+
+/// Steers for the `Driver`. Whoever drives, the autopilot's plan is also
+/// worked out to count the dead ends the car runs into.
+pub fn drive(
+    driver: Res<Driver>,
+    time: Res<Time>,
+    car: Query<(&Car, &Transform)>,
+    obstacles: Query<(&Obstacle, &Transform)>,
+    level_assets: Res<LevelAssets>,
+    mut input: ResMut<ButtonInput<KeyCode>>,
+    mut dead_ends: ResMut<DeadEnds>,
+) {
+    input.release_all();
+    let Ok((car, transform)) = car.get_single() else {
+        return;
+    };
+    let plan = autopilot_plan(car, transform.translation, obstacles.iter(), &level_assets);
+    if !plan.clear && !dead_ends.blocked {
+        dead_ends.count += 1;
+    }
+    dead_ends.blocked = !plan.clear;
+
+    let target = match *driver {
+        Driver::Straight => return,
+        Driver::Weave => {
+            let lane = (time.elapsed_seconds() / WEAVE_SECONDS) as usize % LANES;
+            lane_y(&level_assets.y_values, lane)
+        }
+        Driver::Autopilot => plan.target,
+    };
+    steer_towards(&mut input, transform.translation.y, target);
+}
+
+/// Gameplay without a window, renderer or audio output, stepped by hand with
+/// `App::update`. Each update advances the game by `STEP`. The level is set up
+/// straight away and the race starts on the first update, skipping the
+/// loading screen and countdown.
+pub fn headless_app(level: u8, seed: u64, car: usize, driver: Driver) -> App {
+    let mut app = App::new();
+    app.add_plugins((MinimalPlugins, AssetPlugin::default(), StatesPlugin))
+        .init_asset::<Image>()
+        .init_asset::<AudioSource>()
+        .insert_resource(TimeUpdateStrategy::ManualDuration(STEP))
+        .insert_resource(Level { level })
+        .insert_resource(SelectedCar { index: car })
+        .insert_resource(ObstacleSpawner::seeded(Some(seed)))
+        .insert_resource(driver)
+        .init_resource::<LevelAssets>()
+        .init_resource::<Score>()
+        .init_resource::<SaveData>()
+        .init_resource::<ButtonInput<KeyCode>>()
+        .init_resource::<DeadEnds>()
+        .insert_state(GameState::LoadNextLevel)
+        .add_plugins(GameplayPlugin)
+        .add_systems(
+            Update,
+            drive
+                .before(update_boost)
+                .run_if(in_state(GameState::Running)),
+        );
+
+    app.world_mut().spawn((
+        Transform::default(),
+        OrthographicProjection::default(),
+        CameraMarker,
+    ));
+    app.world_mut().run_system_once(load_level);
+    app.world_mut().run_system_once(reset_power_ups);
+    app.world_mut().run_system_once(spawn_car);
+    app.world_mut()
+        .resource_mut::<NextState<GameState>>()
+        .set(GameState::Running);
+    app
+}
+
+/// How one headless run went.
+#[derive(Clone, Debug)]
+pub struct RunReport {
+    pub level: u8,
+    pub seed: u64,
+    /// World units driven before the run ended.
+    pub distance: f32,
+    pub seconds: f32,
+    pub crashes: u32,
+    pub score: u32,
+    pub cleared: bool,
+    /// Times every way ahead was blocked, see `DeadEnds`.
+    pub dead_ends: u32,
+}
+
+impl RunReport {
+    /// Crashes per 10,000 world units.
+    pub fn crash_rate(&self) -> f32 {
+        if self.distance > 0.0 {
+            self.crashes as f32 * 10_000.0 / self.distance
+        } else {
+            0.0
+        }
+    }
+}
+
+/// Drives `level` until the game ends the race, by a game over or by moving
+/// on to the next level, or until `max_seconds` pass.
+pub fn simulate(level: u8, seed: u64, car: usize, driver: Driver, max_seconds: f32) -> RunReport {
+    let mut app = headless_app(level, seed, car, driver);
+    let steps = (max_seconds / STEP.as_secs_f32()) as u32;
+
+    let mut seconds = 0.0;
+    for _ in 0..steps {
+        app.update();
+        seconds += STEP.as_secs_f32();
+        if race_end(app.world()).is_some() {
+            break;
+        }
+    }
+    report(app.world_mut(), level, seed, seconds)
+}
+
+/// State the game moved on to once the race ended, `Paused` for a game over
+/// and `Unloading` for a cleared level.
+fn race_end(world: &World) -> Option<GameState> {
+    match world.resource::<NextState<GameState>>() {
+        NextState::Pending(state) => Some(state.clone()),
+        NextState::Unchanged => None,
+    }
+}
+
+fn report(world: &mut World, level: u8, seed: u64, seconds: f32) -> RunReport {
+    let mut car = world.query_filtered::<&Transform, With<Car>>();
+    let x = car.single(world).translation.x;
+    let crashes = world.resource::<LevelRun>().crashes;
+    RunReport {
+        level,
+        seed,
+        distance: x.max(0.0),
+        seconds,
+        crashes,
+        score: world.resource::<Score>().score.floor() as u32,
+        cleared: race_end(world) == Some(GameState::Unloading),
+        dead_ends: world.resource::<DeadEnds>().count,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::thread;
+
+    use super::*;
+    use crate::LEVELS;
+
+    /// Seeds per level in `autopilot_clears_every_level`, enough to run into
+    /// a layout with no way through if the spawner can still make one.
+    const SWEEP_SEEDS: u64 = 16;
+
+    #[test]
+    fn autopilot_clears_every_level() {
+        // A run takes around a second in a debug build, so they go in parallel.
+        let reports: Vec<RunReport> = thread::scope(|scope| {
+            let runs: Vec<_> = (1..=LEVELS.len() as u8)
+                .flat_map(|level| (0..SWEEP_SEEDS).map(move |seed| (level, seed)))
+                .map(|(level, seed)| {
+                    scope.spawn(move || simulate(level, seed, 0, Driver::Autopilot, 120.0))
+                })
+                .collect();
+            runs.into_iter()
+                .map(|run| run.join().expect("simulation panicked"))
+                .collect()
+        });
+        for report in reports {
+            assert!(
+                report.cleared && report.dead_ends == 0 && report.crashes == 0,
+                "level {} seed {} not cleared cleanly: {:?}",
+                report.level, report.seed, report
+            );
+        }
+    }
+}
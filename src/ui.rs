@@ -1,91 +1,165 @@
-use bevy::{prelude::*, time::Time};
+use bevy::{
+    prelude::*,
+    render::camera::ScalingMode,
+    time::Time,
+    window::{WindowMode, WindowResized},
+};
 
-use crate::{Car, LevelAssetMarker, LevelAssets};
+use crate::{
+    spawn_dial, BitmapText, Boost, Car, Dial, GameState, LevelAssetMarker, LevelAssets, TextAlign,
+    BOOST_SPEED_FACTOR, BOOST_ZOOM, DEFAULT_STATS,
+};
 
 #[derive(Component)]
 pub struct CameraMarker;
 
+/// Size of the play area in world units. The camera fits it to the window, so
+/// distances and speeds play the same at every resolution.
+pub const WORLD_WIDTH: f32 = 1920.0;
+pub const WORLD_HEIGHT: f32 = 1080.0;
+
 pub fn spawn_camera(mut commands: Commands) {
-    commands.spawn((Camera2dBundle::default(), CameraMarker));
+    let mut camera = Camera2dBundle::default();
+    camera.projection.scaling_mode = ScalingMode::AutoMin {
+        min_width: WORLD_WIDTH,
+        min_height: WORLD_HEIGHT,
+    };
+    commands.spawn((camera, CameraMarker));
+}
+
+/// Window height the HUD's pixel sizes and fonts were laid out for.
+const UI_REFERENCE_HEIGHT: f32 = 720.0;
+
+/// Keeps fonts and pixel borders in proportion with the window. The world
+/// itself is rescaled by the camera's scaling mode.
+pub fn scale_ui_to_window(mut resized: EventReader<WindowResized>, mut ui_scale: ResMut<UiScale>) {
+    if let Some(event) = resized.read().last() {
+        ui_scale.0 = event.height / UI_REFERENCE_HEIGHT;
+    }
+}
+
+pub fn toggle_fullscreen(button_input: Res<ButtonInput<KeyCode>>, mut window: Query<&mut Window>) {
+    let alt = button_input.any_pressed([KeyCode::AltLeft, KeyCode::AltRight]);
+    if !alt || !button_input.just_pressed(KeyCode::Enter) {
+        return;
+    }
+    let mut window = window.single_mut();
+    window.mode = match window.mode {
+        WindowMode::Windowed => WindowMode::BorderlessFullscreen,
+        _ => WindowMode::Windowed,
+    };
 }
 
 pub fn camera_tracking(
     time: Res<Time>,
-    mut camera: Query<&mut Transform, With<CameraMarker>>,
+    mut camera: Query<(&mut Transform, &mut OrthographicProjection), With<CameraMarker>>,
     player: Query<&Car>,
+    boost: Res<Boost>,
 ) {
     if camera.is_empty() || player.is_empty() {
         return;
     }
-    camera.single_mut().translation.x += player.single().speed.x * time.delta_seconds();
+    let (mut transform, mut projection) = camera.single_mut();
+    transform.translation.x += player.single().speed.x * time.delta_seconds();
+
+    // Ease into a slight zoom while boosting so the road rushes past.
+    let target = if boost.active { BOOST_ZOOM } else { 1.0 };
+    projection.scale += (target - projection.scale) * (3.0 * time.delta_seconds()).min(1.0);
+}
+
+/// Widest stretch of world the camera can show, covering ultrawide windows
+/// under `ScalingMode::AutoMin`. Background layers keep this much tiled.
+const MAX_VIEW_WIDTH: f32 = WORLD_WIDTH * 2.0;
+
+/// One scrolling image behind (or in front of) the road, declared per level.
+#[derive(Clone)]
+pub struct BackgroundLayer {
+    pub texture: Handle<Image>,
+    /// How far the layer moves for each unit the camera moves. Below 1 reads
+    /// as distant scenery, above 1 as foreground rushing past.
+    pub scroll_factor: f32,
+    /// Size of a single tile in world units.
+    pub tile_size: Vec2,
+    /// Part of the texture to draw in texels, the whole image when `None`.
+    pub rect: Option<Rect>,
+    pub y: f32,
+    pub z: f32,
+}
+
+impl BackgroundLayer {
+    pub fn new(texture: Handle<Image>, tile_size: Vec2) -> Self {
+        BackgroundLayer {
+            texture,
+            scroll_factor: 1.0,
+            tile_size,
+            rect: None,
+            y: 0.0,
+            z: 0.0,
+        }
+    }
+
+    fn tile_count(&self) -> i32 {
+        (MAX_VIEW_WIDTH / self.tile_size.x).ceil() as i32 + 1
+    }
+
+    /// Slot a tile should move to with the camera at `camera_x`, and the world x
+    /// of its left edge. Slots that fell behind the view wrap to the front, so
+    /// the ring always covers `MAX_VIEW_WIDTH` around the camera without seams.
+    fn place(&self, slot: i32, camera_x: f32) -> (i32, f32) {
+        let first = (camera_x * self.scroll_factor / self.tile_size.x).floor() as i32;
+        let slot = first + (slot - first).rem_euclid(self.tile_count());
+        let offset = slot as f32 * self.tile_size.x - MAX_VIEW_WIDTH / 2.0;
+        (slot, camera_x * (1.0 - self.scroll_factor) + offset)
+    }
 }
 
 #[derive(Component)]
-pub struct Background;
+pub struct Background {
+    layer: usize,
+    /// Position in the layer counted in whole tiles, kept as an integer so
+    /// neighbouring tiles never drift apart.
+    slot: i32,
+}
 
 pub fn spawn_background(
     mut commands: Commands,
-    window: Query<&Window>,
     level_assets: ResMut<LevelAssets>,
-    asset_server: ResMut<AssetServer>,
+    camera: Query<&Transform, With<CameraMarker>>,
 ) {
-    let width = window.single().width();
-    let height = window.single().height();
-
-    commands.spawn((
-        SpriteBundle {
-            texture: level_assets.background_texture.clone(),
-            sprite: Sprite {
-                custom_size: Some(Vec2 {
-                    x: width * 3.0,
-                    y: height,
-                }),
-                anchor: bevy::sprite::Anchor::CenterLeft,
-                ..default()
-            },
-            transform: Transform::from_xyz(0.0, 0.0, 0.0),
-            ..default()
-        },
-        ImageScaleMode::Tiled {
-            tile_x: true,
-            tile_y: false,
-            stretch_value: height / 1080.0,
-        },
-        Background,
-        LevelAssetMarker,
-    ));
-    commands.spawn((
-        SpriteBundle {
-            texture: level_assets.background_texture.clone(),
-            sprite: Sprite {
-                custom_size: Some(Vec2 {
-                    x: width,
-                    y: height,
-                }),
-                anchor: bevy::sprite::Anchor::CenterLeft,
-                ..default()
-            },
-            transform: Transform::from_xyz(width * -1.0, 0.0, 0.0),
-            ..default()
-        },
-        ImageScaleMode::Tiled {
-            tile_x: false,
-            tile_y: false,
-            stretch_value: height / 1080.0,
-        },
-        Background,
-        LevelAssetMarker,
-    ));
-    for n in 1..5 {
+    let camera_x = camera.single().translation.x;
+    for (index, layer) in level_assets.background_layers.iter().enumerate() {
+        for n in 0..layer.tile_count() {
+            let (slot, x) = layer.place(n, camera_x);
+            commands.spawn((
+                SpriteBundle {
+                    texture: layer.texture.clone(),
+                    sprite: Sprite {
+                        custom_size: Some(layer.tile_size),
+                        rect: layer.rect,
+                        anchor: bevy::sprite::Anchor::CenterLeft,
+                        ..default()
+                    },
+                    transform: Transform::from_xyz(x, layer.y, layer.z),
+                    ..default()
+                },
+                Background { layer: index, slot },
+                LevelAssetMarker,
+            ));
+        }
+    }
+    for n in 1..=level_assets.laps {
         commands.spawn((
             SpriteBundle {
-                texture: asset_server.load("1077.png"),
+                texture: level_assets.lap_texture.clone(),
                 sprite: Sprite {
-                    custom_size: Some(Vec2 { x: 64.0, y: height }),
+                    custom_size: Some(Vec2 {
+                        x: 64.0,
+                        y: WORLD_HEIGHT,
+                    }),
                     anchor: bevy::sprite::Anchor::CenterLeft,
                     ..default()
                 },
-                transform: Transform::from_xyz((n as f32) * width * 10.0, 0.0, 2.0),
+                transform: Transform::from_xyz(n as f32 * level_assets.lap_length, 0.0, 2.0),
                 ..default()
             },
             ImageScaleMode::Tiled {
@@ -93,259 +167,289 @@ pub fn spawn_background(
                 tile_y: true,
                 stretch_value: 1.0,
             },
+            LevelAssetMarker,
         ));
     }
 }
 
+/// Moves tiles that scrolled out behind the camera to the front of their
+/// layer, so each layer is a fixed set of entities.
 pub fn update_background(
-    mut commands: Commands,
     camera: Query<&Transform, (With<CameraMarker>, Without<Background>)>,
-    backgrounds: Query<(Entity, &Transform), (With<Background>, Without<CameraMarker>)>,
-    window: Query<&Window>,
+    mut backgrounds: Query<(&mut Background, &mut Transform), Without<CameraMarker>>,
     level_assets: Res<LevelAssets>,
 ) {
-    let width = window.single().width();
-    let height = window.single().height();
+    let camera_x = camera.single().translation.x;
 
-    for (background_entity, background_transform) in backgrounds.iter() {
-        if background_transform.translation.x < camera.single().translation.x - width * 4.0 {
-            commands.entity(background_entity).despawn();
+    for (mut tile, mut transform) in backgrounds.iter_mut() {
+        let Some(layer) = level_assets.background_layers.get(tile.layer) else {
+            continue;
+        };
+        let (slot, x) = layer.place(tile.slot, camera_x);
+        if tile.slot != slot {
+            tile.slot = slot;
         }
+        transform.translation.x = x;
     }
-    let road_remaining = backgrounds.iter().any(|(_, background_transform)| {
-        background_transform.translation.x > camera.single().translation.x
-    });
-    if !road_remaining {
-        let road_length_factor = (camera.single().translation.x / (width * 3.0)).floor() + 1.0;
+}
 
-        commands.spawn((
-            SpriteBundle {
-                texture: level_assets.background_texture.clone(), // Background
-                sprite: Sprite {
-                    custom_size: Some(Vec2 {
-                        x: width * 3.0,
-                        y: height,
-                    }),
-                    anchor: bevy::sprite::Anchor::CenterLeft,
-                    ..default()
-                },
-                transform: Transform::from_xyz(road_length_factor * 3.0 * width, 0.0, 0.0),
-                ..default()
-            },
-            ImageScaleMode::Tiled {
-                tile_x: true,
-                tile_y: false,
-                stretch_value: height / 1080.0,
-            },
-            Background,
-            LevelAssetMarker,
-        ));
+/// Marks each root of the in-race HUD. They are spawned once and their
+/// children are only touched when the value they show changes.
+#[derive(Component)]
+pub struct Hud;
+
+/// Shows the HUD while racing and hides it on menus and loading screens.
+pub fn show_hud(state: Res<State<GameState>>, mut hud: Query<&mut Visibility, With<Hud>>) {
+    if !state.is_changed() {
+        return;
+    }
+    let visibility = match state.get() {
+        GameState::Countdown | GameState::Running => Visibility::Inherited,
+        _ => Visibility::Hidden,
+    };
+    for mut root in hud.iter_mut() {
+        root.set_if_neq(visibility);
     }
 }
 
-pub fn spawn_ui(mut commands: Commands, asset_server: Res<AssetServer>) {
-    commands.spawn(
-        TextBundle::from_section(
-            "LAPS",
-            TextStyle {
-                font_size: 45.0,
-                font: asset_server.load("GohuFont11NerdFont-Regular.ttf"),
-                ..default()
-            },
-        )
-        .with_text_justify(JustifyText::Left)
-        .with_style(Style {
-            position_type: PositionType::Absolute,
-            margin: UiRect::horizontal(Val::Auto),
-            top: Val::Percent(50.0 / 1080.0 * 100.0),
-            left: Val::Percent(10.0 / 1920.0 * 100.0),
-            width: Val::Percent(5.0),
-            height: Val::Percent(5.0),
-            ..Default::default()
-        }),
-    );
-    commands.insert_resource(Lap {
-        lap: 0,
-        font: asset_server.load("GohuFont11NerdFont-Regular.ttf"),
-    });
+#[derive(Component)]
+pub struct LapsText;
 
-    commands.spawn(
-        TextBundle::from_section(
-            "MPH",
-            TextStyle {
-                font_size: 45.0,
-                font: asset_server.load("GohuFont11NerdFont-Regular.ttf"),
-                ..default()
-            },
-        )
-        .with_text_justify(JustifyText::Left)
-        .with_style(Style {
-            position_type: PositionType::Absolute,
-            margin: UiRect::horizontal(Val::Auto),
-            top: Val::Percent(115.0 / 1080.0 * 100.0),
-            left: Val::Percent(10.0 / 1920.0 * 100.0),
-            width: Val::Percent(6.0),
-            height: Val::Percent(5.0),
-            ..Default::default()
-        }),
-    );
-}
+#[derive(Component)]
+pub struct SpeedText;
 
-#[derive(Resource)]
-pub struct Score {
-    digits: Vec<Handle<Image>>,
-    pub score: f32,
-}
+#[derive(Component)]
+pub struct ScoreText;
 
 #[derive(Component)]
-pub struct ScoreDigit;
+pub struct SpeedDial;
 
-pub fn spawn_score(mut commands: Commands, asset_server: Res<AssetServer>) {
-    let mut digits: Vec<Handle<Image>> = Vec::new();
-    for n in 0..10 {
-        digits.push(asset_server.load((1687 + n).to_string() + ".png"));
-    }
-    commands.insert_resource(Score { digits, score: 0.0 });
+/// Optional HUD widgets the player can switch on and off.
+#[derive(Resource)]
+pub struct HudOptions {
+    pub speed_dial: bool,
 }
 
-pub fn update_score(
-    mut commands: Commands,
-    cars: Query<&Car>,
-    score: Res<Score>,
-    prev_score_digits: Query<Entity, With<ScoreDigit>>,
-    window: Query<&Window>,
-) {
-    if cars.is_empty() {
-        return;
+impl Default for HudOptions {
+    fn default() -> Self {
+        HudOptions { speed_dial: true }
     }
+}
 
-    for prev_digit in prev_score_digits.iter() {
-        commands.entity(prev_digit).despawn();
+fn hud_label(value: &str, font: Handle<Font>, style: Style) -> TextBundle {
+    TextBundle::from_section(
+        value,
+        TextStyle {
+            font_size: 45.0,
+            font,
+            ..default()
+        },
+    )
+    .with_text_justify(JustifyText::Left)
+    .with_style(Style {
+        position_type: PositionType::Absolute,
+        margin: UiRect::horizontal(Val::Auto),
+        ..style
+    })
+}
+
+fn hud_value(style: Style) -> NodeBundle {
+    NodeBundle {
+        style: Style {
+            position_type: PositionType::Absolute,
+            ..style
+        },
+        ..default()
     }
+}
 
-    let width = window.single().width();
-    let player_score = score.score.floor() as u32;
-    let score_string = player_score.to_string();
-    let temp = score_string.chars();
-    let num_digits = temp.clone().count();
-    let mut left_pos = width - 10.0 - (0.03 * width * num_digits as f32);
+pub fn spawn_ui(mut commands: Commands, asset_server: Res<AssetServer>) {
+    let font: Handle<Font> = asset_server.load("GohuFont11NerdFont-Regular.ttf");
+    commands.insert_resource(Lap { lap: 0 });
 
-    for char in temp {
-        let digit = char.to_digit(10).unwrap_or_default();
-        commands.spawn((
-            ImageBundle {
+    commands
+        .spawn((
+            NodeBundle {
                 style: Style {
                     position_type: PositionType::Absolute,
-                    margin: UiRect::horizontal(Val::Auto),
+                    width: Val::Percent(100.0),
+                    height: Val::Percent(100.0),
+                    ..default()
+                },
+                ..default()
+            },
+            Hud,
+        ))
+        .with_children(|hud| {
+            hud.spawn(hud_label(
+                "LAPS",
+                font.clone(),
+                Style {
                     top: Val::Percent(50.0 / 1080.0 * 100.0),
-                    left: Val::Px(left_pos),
-                    width: Val::Percent(3.0),
+                    left: Val::Percent(10.0 / 1920.0 * 100.0),
+                    width: Val::Percent(5.0),
+                    height: Val::Percent(5.0),
+                    ..default()
+                },
+            ));
+            hud.spawn((
+                hud_value(Style {
+                    top: Val::Percent(4.0),
+                    left: Val::Percent(8.0),
+                    ..default()
+                }),
+                BitmapText::new("", 5.0).with_kerning(0.1),
+                LapsText,
+            ));
+            hud.spawn(hud_label(
+                "MPH",
+                font,
+                Style {
+                    top: Val::Percent(115.0 / 1080.0 * 100.0),
+                    left: Val::Percent(10.0 / 1920.0 * 100.0),
+                    width: Val::Percent(6.0),
                     height: Val::Percent(5.0),
-                    ..Default::default()
+                    ..default()
                 },
-                image: UiImage {
-                    texture: score.digits[digit as usize].clone(),
+            ));
+            hud.spawn((
+                hud_value(Style {
+                    top: Val::Percent(10.5),
+                    left: Val::Percent(7.0),
+                    width: Val::Percent(8.0),
+                    ..default()
+                }),
+                BitmapText::new("0", 4.0)
+                    .with_kerning(0.1)
+                    .with_align(TextAlign::Center),
+                SpeedText,
+            ));
+            hud.spawn((
+                hud_value(Style {
+                    top: Val::Percent(50.0 / 1080.0 * 100.0),
+                    right: Val::Percent(10.0 / 1920.0 * 100.0),
+                    width: Val::Percent(40.0),
+                    ..default()
+                }),
+                BitmapText::new("0", 5.0).with_align(TextAlign::Right),
+                ScoreText,
+            ));
+            spawn_dial(
+                hud,
+                Style {
+                    position_type: PositionType::Absolute,
+                    left: Val::Percent(1.0),
+                    bottom: Val::Percent(3.0),
+                    width: Val::Vh(20.0),
+                    height: Val::Vh(20.0),
                     ..default()
                 },
-                ..Default::default()
-            },
-            ScoreDigit,
-        ));
-        left_pos += 0.03 * width;
-    }
+                Dial {
+                    value: 0.0,
+                    max: DEFAULT_STATS.max_speed * BOOST_SPEED_FACTOR,
+                    redline: DEFAULT_STATS.max_speed,
+                    highlight: false,
+                },
+            )
+            .insert(SpeedDial);
+        });
 }
 
-#[derive(Component)]
-pub struct LapsDigit;
+#[derive(Resource, Default)]
+pub struct Score {
+    pub score: f32,
+}
+
+pub fn update_score(
+    score: Res<Score>,
+    mut shown: Local<Option<u32>>,
+    mut text: Query<&mut BitmapText, With<ScoreText>>,
+) {
+    let player_score = score.score.floor() as u32;
+    if *shown == Some(player_score) {
+        return;
+    }
+    *shown = Some(player_score);
+    text.single_mut().set_value(player_score.to_string());
+}
 
 #[derive(Resource)]
 pub struct Lap {
     pub lap: usize,
-    pub font: Handle<Font>,
 }
 
 pub fn update_laps(
-    mut commands: Commands,
     car: Query<&Transform, With<Car>>,
     mut lap: ResMut<Lap>,
-    window: Query<&Window>,
-    prev_laps_digit: Query<Entity, With<LapsDigit>>,
+    level_assets: Res<LevelAssets>,
 ) {
-    let width = window.single().width();
-    let digit = car.single().translation.x / width / 10.0;
-
-    if digit as usize > lap.lap || prev_laps_digit.is_empty() {
-        lap.lap = digit as usize;
-
-        for prev_digit in prev_laps_digit.iter() {
-            commands.entity(prev_digit).despawn();
-        }
-
-        commands.spawn((
-            TextBundle::from_section(
-                lap.lap.clone().to_string(),
-                TextStyle {
-                    font_size: 60.0,
-                    font: lap.font.clone(),
-                    ..default()
-                },
-            )
-            .with_text_justify(JustifyText::Left)
-            .with_style(Style {
-                position_type: PositionType::Absolute,
-                margin: UiRect::horizontal(Val::Auto),
-                top: Val::Percent(3.5),
-                left: Val::Percent(8.0),
-                width: Val::Percent(3.0),
-                height: Val::Percent(5.0),
-                ..Default::default()
-            }),
-            LapsDigit,
-            LevelAssetMarker,
-        ));
+    let completed = level_assets.laps_completed(car.single().translation.x) as usize;
+    if completed != lap.lap {
+        lap.lap = completed;
     }
 }
 
-#[derive(Component)]
-pub struct SpeedDigit;
-
-pub fn update_speed(
-    mut commands: Commands,
-    cars: Query<&Car>,
+pub fn update_laps_text(
     lap: Res<Lap>,
-    prev_speed_digits: Query<Entity, With<SpeedDigit>>,
+    level_assets: Res<LevelAssets>,
+    mut text: Query<&mut BitmapText, With<LapsText>>,
 ) {
-    for prev_digit in prev_speed_digits.iter() {
-        commands.entity(prev_digit).despawn();
+    if !lap.is_changed() && !level_assets.is_changed() {
+        return;
     }
+    text.single_mut()
+        .set_value(format!("{}/{}", lap.lap, level_assets.laps));
+}
 
-    let mut player_speed = 0;
-    if !cars.is_empty() {
-        player_speed = (cars.single().speed.x / 10.0).floor() as u32;
+pub fn update_speed(
+    cars: Query<&Car, Changed<Car>>,
+    mut shown: Local<Option<u32>>,
+    mut text: Query<&mut BitmapText, With<SpeedText>>,
+) {
+    let Ok(car) = cars.get_single() else {
+        return;
+    };
+    let player_speed = (car.speed.x / 10.0).floor() as u32;
+    if *shown == Some(player_speed) {
+        return;
     }
+    *shown = Some(player_speed);
+    text.single_mut().set_value(player_speed.to_string());
+}
 
-    commands.spawn((
-        TextBundle::from_section(
-            player_speed.to_string(),
-            TextStyle {
-                font_size: 50.0,
-                font: lap.font.clone(),
-                ..default()
-            },
-        )
-        .with_text_justify(JustifyText::Left)
-        .with_style(Style {
-            position_type: PositionType::Absolute,
-            margin: UiRect::horizontal(Val::Auto),
-            top: Val::Percent(10.0),
-            left: Val::Percent(7.0),
-            width: Val::Percent(3.0),
-            height: Val::Percent(5.0),
-            ..Default::default()
-        }),
-        SpeedDigit,
-        LevelAssetMarker,
-    ));
+/// Drives the dial from the car, redlining at the car's normal top speed so
+/// anything past it is boost.
+pub fn update_speed_dial(
+    cars: Query<&Car>,
+    boost: Res<Boost>,
+    mut dial: Query<&mut Dial, With<SpeedDial>>,
+) {
+    let Ok(car) = cars.get_single() else {
+        return;
+    };
+    dial.single_mut().set_if_neq(Dial {
+        value: car.speed.x,
+        max: car.stats.max_speed * BOOST_SPEED_FACTOR,
+        redline: car.stats.max_speed,
+        highlight: boost.active,
+    });
+}
+
+pub fn toggle_speed_dial(
+    button_input: Res<ButtonInput<KeyCode>>,
+    mut options: ResMut<HudOptions>,
+    mut dial: Query<&mut Visibility, With<SpeedDial>>,
+) {
+    if button_input.just_pressed(KeyCode::F2) {
+        options.speed_dial = !options.speed_dial;
+    }
+    if options.is_changed() {
+        *dial.single_mut() = if options.speed_dial {
+            Visibility::Inherited
+        } else {
+            Visibility::Hidden
+        };
+    }
 }
 
 #[derive(Component)]
@@ -363,3 +467,117 @@ pub fn start_music(mut commands: Commands, assets: Res<LevelAssets>, music: Quer
         ));
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn background_app() -> App {
+        let layer = |scroll_factor: f32, width: f32| BackgroundLayer {
+            scroll_factor,
+            ..BackgroundLayer::new(Handle::default(), Vec2::new(width, WORLD_HEIGHT))
+        };
+        let mut app = App::new();
+        app.add_plugins(MinimalPlugins)
+            .insert_resource(LevelAssets {
+                background_layers: vec![layer(1.0, 1920.0), layer(0.4, 1582.0), layer(1.25, 640.0)],
+                ..default()
+            })
+            .add_systems(Startup, spawn_background)
+            .add_systems(Update, update_background);
+        app.world_mut().spawn((Transform::default(), CameraMarker));
+        app
+    }
+
+    fn move_camera(app: &mut App, x: f32) {
+        let world = app.world_mut();
+        world
+            .query_filtered::<&mut Transform, With<CameraMarker>>()
+            .single_mut(world)
+            .translation
+            .x = x;
+        app.update();
+    }
+
+    fn tiles(app: &mut App) -> Vec<(Entity, usize, f32)> {
+        let world = app.world_mut();
+        let mut tiles: Vec<(Entity, usize, f32)> = world
+            .query::<(Entity, &Background, &Transform)>()
+            .iter(world)
+            .map(|(entity, tile, transform)| (entity, tile.layer, transform.translation.x))
+            .collect();
+        tiles.sort_by_key(|(entity, _, _)| *entity);
+        tiles
+    }
+
+    /// Every layer's tiles must butt up against each other and span the widest
+    /// view the camera can have around `camera_x`.
+    fn assert_covered(app: &mut App, camera_x: f32) {
+        let layers = app
+            .world()
+            .resource::<LevelAssets>()
+            .background_layers
+            .clone();
+        let tiles = tiles(app);
+        // Sub-pixel slack for f32 rounding far down the road.
+        let tolerance = 1.0;
+        for (index, layer) in layers.iter().enumerate() {
+            let mut edges: Vec<f32> = tiles
+                .iter()
+                .filter(|(_, tile_layer, _)| *tile_layer == index)
+                .map(|(_, _, x)| *x)
+                .collect();
+            edges.sort_by(f32::total_cmp);
+
+            let view_left = camera_x - MAX_VIEW_WIDTH / 2.0;
+            let view_right = camera_x + MAX_VIEW_WIDTH / 2.0;
+            assert!(
+                edges[0] <= view_left + tolerance,
+                "gap at the left edge at {camera_x}"
+            );
+            for pair in edges.windows(2) {
+                assert!(
+                    (pair[1] - pair[0] - layer.tile_size.x).abs() <= tolerance,
+                    "gap between tiles at {camera_x}"
+                );
+            }
+            assert!(
+                edges[edges.len() - 1] + layer.tile_size.x >= view_right - tolerance,
+                "gap at the right edge at {camera_x}"
+            );
+        }
+    }
+
+    #[test]
+    fn background_stays_covered_over_long_distances() {
+        let mut app = background_app();
+        app.update();
+        let pooled = tiles(&mut app);
+
+        let mut camera_x = 0.0;
+        for step in 0..5000 {
+            // Uneven steps so tiles wrap at every possible phase, plus the odd
+            // big jump like a frame hitch.
+            camera_x += if step % 500 == 499 {
+                25_000.0
+            } else {
+                117.3 + (step % 7) as f32 * 31.0
+            };
+            move_camera(&mut app, camera_x);
+            assert_covered(&mut app, camera_x);
+        }
+
+        let recycled = tiles(&mut app);
+        assert_eq!(
+            pooled
+                .iter()
+                .map(|(entity, _, _)| *entity)
+                .collect::<Vec<_>>(),
+            recycled
+                .iter()
+                .map(|(entity, _, _)| *entity)
+                .collect::<Vec<_>>(),
+            "tiles should be reused, not respawned"
+        );
+    }
+}